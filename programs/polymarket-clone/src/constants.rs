@@ -11,6 +11,15 @@ pub const REQUEST_QUEUE_SEED: &[u8] = b"request_queue";
 pub const POSITION_SEED: &[u8] = b"position";
 pub const OPEN_ORDER_SEED: &[u8] = b"open_order";
 pub const FEE_VAULT_USDC: &[u8] = b"fee_vault_usdc";
+pub const CRANKER_LEDGER_SEED: &[u8] = b"cranker_ledger";
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+pub const BOND_VAULT_SEED: &[u8] = b"bond_vault";
+pub const OUTCOME_MINT_SEED: &[u8] = b"outcome_mint";
+pub const OUTCOME_VAULT_SEED: &[u8] = b"outcome_vault";
+pub const COMMITTEE_SEED: &[u8] = b"committee";
+pub const GLOBAL_CONFIG_SEED: &[u8] = b"global_config";
+pub const TRADER_STATS_SEED: &[u8] = b"trader_stats";
+pub const REFERRER_LEDGER_SEED: &[u8] = b"referrer_ledger";
 // pub const MAX_ORDER_PER_TRADER: usize = 16;
 // pub const MAX_SLAB_NODES: usize = 1024; //these are max orders stored on a single slab
 // pub const MAX_EVENTS: usize = 128;
@@ -27,7 +36,20 @@ pub const MAX_ORDER_ENTRIES: usize = 2048;
 pub const MAX_OPEN_ORDER_SLOTS: usize = 32;
 pub const MAX_MARKET_QUESTION: usize = 256;
 pub const MAX_MARKET_DESC: usize = 1024;
+pub const MAX_OUTCOMES: usize = 16;
+pub const MAX_COMMITTEE_MEMBERS: usize = 10;
+// a proposed member set can't be replaced by a different one for this long
+pub const ROTATION_PROPOSAL_TTL_SECS: i64 = 3 * 86_400;
+pub const MAX_FEE_TIERS: usize = 8;
+
+// fee tiers look at taker notional over this many daily buckets
+pub const VOLUME_WINDOW_DAYS: usize = 30;
+pub const SECONDS_PER_DAY: i64 = 86_400;
 
 pub const FEE_BPS: u16 = 30; //0.3%
 pub const CRANKER_REWARD_BPS: u16 = 50; //0.5%
 pub const PRICE_PRECISION_SCALE: u128 = 1_000_000;
+
+// compute kept in reserve before starting another request / event so the crank never runs out mid-item
+pub const MATCH_REQUEST_CU_RESERVE: u64 = 60_000;
+pub const CONSUME_EVENT_CU_RESERVE: u64 = 80_000;
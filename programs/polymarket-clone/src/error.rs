@@ -29,4 +29,52 @@ pub enum MarketError {
     InvalidSide,
     #[msg("Vault Transfer Failed")]
     VaultTransferFailed,
+    #[msg("Market is Paused")]
+    MarketPaused,
+    #[msg("Market has not Closed yet")]
+    MarketNotClosed,
+    #[msg("Invalid Market Status for this operation")]
+    InvalidMarketStatus,
+    #[msg("Market does not use this Resolution Mode")]
+    InvalidResolutionMode,
+    #[msg("Proposal Liveness Window has not Elapsed")]
+    LivenessNotElapsed,
+    #[msg("Proposal Liveness Window has Elapsed")]
+    LivenessElapsed,
+    #[msg("Proposal is Already Disputed")]
+    AlreadyDisputed,
+    #[msg("Proposal is Not Disputed")]
+    NotDisputed,
+    #[msg("Price Feed Account is Invalid")]
+    InvalidPriceFeed,
+    #[msg("Price is Stale")]
+    StalePrice,
+    #[msg("Price Confidence Interval is Too Wide")]
+    PriceConfidenceTooWide,
+    #[msg("Outcome does not belong to this Market")]
+    InvalidOutcome,
+    #[msg("Payout Vector is Invalid")]
+    InvalidPayoutVector,
+    #[msg("Open Order still holds Funds or Orders")]
+    OpenOrderNotEmpty,
+    #[msg("Resolution Challenge Period is still Active")]
+    ChallengePeriodActive,
+    #[msg("Resolution Challenge Period has Elapsed")]
+    ChallengePeriodElapsed,
+    #[msg("Resolution Deadline has not Passed")]
+    ResolutionDeadlineNotReached,
+    #[msg("Committee Members or Threshold are Invalid")]
+    InvalidCommittee,
+    #[msg("Signer is not a Committee Member")]
+    NotCommitteeMember,
+    #[msg("Vault USDC does not cover Escrowed Quote and Outcome Liabilities")]
+    MarketInsolvent,
+    #[msg("Outcome Mint Supply exceeds Market Accounting")]
+    SupplyMismatch,
+    #[msg("Maker Fee must lie between minus the Taker Fee and the Taker Fee")]
+    InvalidFeeSchedule,
+    #[msg("A different Committee Rotation is still Pending")]
+    RotationPending,
+    #[msg("Referrer has no Ledger on this Market")]
+    ReferrerLedgerMissing,
 }
@@ -0,0 +1,233 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{FeeCurve, MarketStatus};
+
+#[repr(u8)]
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CrankType {
+    MatchRequest = 0,
+    ConsumeEvents = 1,
+}
+
+// returned through set_return_data and emitted so crank bots can size their next transaction
+#[event]
+#[derive(Debug, Clone, Copy)]
+pub struct CrankProgress {
+    pub market: Pubkey,
+    pub crank_type: CrankType,
+    pub processed: u16,
+    pub remaining: u64,
+    pub compute_exhausted: bool,
+}
+
+// every event below carries the market's sequence number so consumers can detect gaps
+#[event]
+pub struct MarketCreated {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub market_id: u64,
+    pub creator: Pubkey,
+    pub question: String,
+    pub num_outcomes: u8,
+    pub end_ts: i64,
+    pub maker_fee_bps: i16,
+    pub taker_fee_bps: u16,
+    pub fee_curve: FeeCurve,
+}
+
+#[event]
+pub struct OutcomeAdded {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub outcome: u8,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct OrderAccepted {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub open_order: Pubkey,
+    pub order_id: u64,
+    pub client_id: u64,
+    pub request_type: u8,
+    pub side: u8,
+    pub outcome: u8,
+    pub price: u128,
+    pub quantity: u64,
+    pub owner_slot: u16,
+}
+
+#[event]
+pub struct OrderRested {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub open_order: Pubkey,
+    pub order_id: u64,
+    pub side: u8,
+    pub outcome: u8,
+    pub price: u128,
+    pub quantity: u64,
+    pub owner_slot: u16,
+}
+
+#[event]
+pub struct OrderFilled {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub maker_open_orders: Pubkey,
+    pub taker_open_orders: Pubkey,
+    pub order_id: u64,
+    pub taker_side: u8,
+    pub outcome: u8,
+    pub price: u128,
+    pub quantity: u64,
+    pub taker_fee: u64,
+    pub maker_fee: i64, // negative when the maker was paid a rebate
+    pub referral_fee: u64,
+    pub is_amm: bool,
+}
+
+#[event]
+pub struct OrderCancelled {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub open_order: Pubkey,
+    pub order_id: u64,
+    pub side: u8,
+    pub outcome: u8,
+    pub price: u128,
+    pub quantity: u64,
+    pub owner_slot: u16,
+}
+
+#[event]
+pub struct FundsSettled {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub open_order: Pubkey,
+    pub amount: u64,
+    pub base_amount: u64,
+}
+
+#[event]
+pub struct TokensSplit {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub trader: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct TokensMerged {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub trader: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct MarketResolved {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub resolver: Pubkey,
+    pub status: MarketStatus,
+    pub payout_numerators: Vec<u64>,
+    pub payout_denominator: u64,
+    pub claimable_at: i64,
+}
+
+#[event]
+pub struct RewardClaimed {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub claimer: Pubkey,
+    pub outcome_mint: Pubkey,
+    pub amount: u64,
+    pub payout: u64,
+}
+
+#[event]
+pub struct MarketStatusChanged {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub status: MarketStatus,
+}
+
+#[event]
+pub struct ResolutionProposed {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub proposer: Pubkey,
+    pub outcome: u8,
+    pub bond: u64,
+    pub liveness_ends: i64,
+}
+
+#[event]
+pub struct ResolutionDisputed {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub disputer: Pubkey,
+    pub outcome: u8,
+    pub bond: u64,
+}
+
+#[event]
+pub struct BondSettled {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ResolutionVoteCast {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub member: Pubkey,
+    pub outcome: u8,
+    pub votes: u8,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct CommitteeRotated {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct SolvencyAudited {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub vault_balance: u64,
+    pub quote_escrow: u64,
+    pub outcome_liability: u64,
+    pub amm_issued: u64,
+    pub solvent: bool,
+}
+
+#[event]
+pub struct FeesCollected {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub protocol_amount: u64,
+    pub creator_amount: u64,
+    pub insurance_amount: u64,
+}
+
+#[event]
+pub struct FeedPriceSnapshotted {
+    pub seq: u64,
+    pub market: Pubkey,
+    pub price: i64,
+    pub expo: i32,
+    pub conf: u64,
+    pub publish_time: i64,
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    constants::{OUTCOME_MINT_SEED, OUTCOME_VAULT_SEED},
+    error::MarketError,
+    events::OutcomeAdded,
+    state::{Market, MarketStatus},
+};
+
+#[derive(Accounts)]
+pub struct AddOutcome<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(mut , has_one = creator @ MarketError::Unauthorized)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = creator,
+        mint::decimals = 6,
+        mint::authority = market,
+        seeds = [OUTCOME_MINT_SEED , market.key().as_ref() , &[market.registered_outcomes]],
+        bump
+    )]
+    pub outcome_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = creator,
+        token::mint = outcome_mint,
+        token::authority = market,
+        seeds = [OUTCOME_VAULT_SEED , market.key().as_ref() , &[market.registered_outcomes]],
+        bump
+    )]
+    pub outcome_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn add_outcome_handler(ctx: Context<AddOutcome>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    require!(
+        market.status == MarketStatus::Pending,
+        MarketError::InvalidMarketStatus
+    );
+    require!(
+        market.registered_outcomes < market.num_outcomes,
+        MarketError::InvalidArgument
+    );
+
+    let outcome = market.registered_outcomes;
+    market.outcome_mints[outcome as usize] = ctx.accounts.outcome_mint.key();
+    market.outcome_vaults[outcome as usize] = ctx.accounts.outcome_vault.key();
+    market.registered_outcomes += 1;
+    // the last registration opens the market if start_ts has already passed
+    market.refresh_status(Clock::get()?.unix_timestamp);
+
+    emit!(OutcomeAdded {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        outcome,
+        mint: market.outcome_mints[outcome as usize],
+        vault: market.outcome_vaults[outcome as usize],
+    });
+
+    Ok(())
+}
@@ -1,218 +1,253 @@
-use crate::{constants::PRICE_PRECISION_SCALE, error::MarketError, state::OutcomeSide};
+use crate::{
+    constants::{MAX_OUTCOMES, PRICE_PRECISION_SCALE},
+    error::MarketError,
+};
 use anchor_lang::prelude::*;
 
 pub const FP_SCALE: u128 = PRICE_PRECISION_SCALE;
 
-pub fn amm_cost_to_yes(q_yes: u128, q_no: u128, b: u128, delta_q: u128) -> Result<u128> {
-    let mut c1 = lsmr_cost(
-        q_yes.checked_add(delta_q).ok_or(MarketError::MathError)?,
-        q_no,
-        b,
-    )?;
-    let c0 = lsmr_cost(q_yes, q_no, b)?;
-    c1 = c1.checked_sub(c0).ok_or(MarketError::MathError)?;
-    Ok(c1)
+// the cost function is evaluated at 18 decimals so the rounding stays far below one base unit
+const WAD: u128 = 1_000_000_000_000_000_000;
+const LN_2_WAD: u128 = 693_147_180_559_945_309;
+
+// quantities and b are in outcome token base units, which pay out 1:1 in USDC base units, so the
+// returned cost is in USDC base units, rounded up in the AMM's favour
+pub fn amm_cost_to_buy(q: &[u128], b: u128, outcome: u8, delta_q: u128) -> Result<u128> {
+    let mut q_after = [0u128; MAX_OUTCOMES];
+    let q_after = &mut q_after[..q.len()];
+    q_after.copy_from_slice(q);
+    let index = outcome as usize;
+    require!(index < q.len(), MarketError::InvalidOutcome);
+    q_after[index] = q_after[index]
+        .checked_add(delta_q)
+        .ok_or(MarketError::MathError)?;
+
+    let c1 = lsmr_cost(q_after, b)?;
+    let c0 = lsmr_cost(q, b)?;
+    let cost = c1.checked_sub(c0).ok_or(MarketError::MathError)?;
+    Ok(cost.div_ceil(WAD))
 }
 
-pub fn amm_cost_to_no(q_yes: u128, q_no: u128, b: u128, delta_q: u128) -> Result<u128> {
-    let mut c1 = lsmr_cost(
-        q_yes,
-        q_no.checked_add(delta_q).ok_or(MarketError::MathError)?,
-        b,
-    )?;
-    let c0 = lsmr_cost(q_yes, q_no, b)?;
-    c1 = c1.checked_sub(c0).ok_or(MarketError::MathError)?;
-    Ok(c1)
-}
-
-pub fn lsmr_cost(q_yes: u128, q_no: u128, b: u128) -> Result<u128> {
-    if b == 0 {
-        return err!(MarketError::InvalidArgument);
-    }
-
-    let x_yes = q_yes
-        .checked_mul(FP_SCALE)
-        .ok_or(MarketError::MathError)?
-        .checked_div(b)
-        .ok_or(MarketError::MathError)?;
-    let x_no = q_no
-        .checked_mul(FP_SCALE)
+// C(q) = b * ln(sum_i exp(q_i / b)) over every outcome of the market, scaled by WAD; written as
+// m + b * ln(sum_i exp((q_i - m) / b)) with m = max q_i so every exponent is at most zero
+pub fn lsmr_cost(q: &[u128], b: u128) -> Result<u128> {
+    require!(b > 0, MarketError::InvalidArgument);
+    let m = q
+        .iter()
+        .copied()
+        .max()
+        .ok_or(MarketError::InvalidArgument)?;
+
+    let sum = sum_exp(q, m, b)?;
+    let cost = m
+        .checked_mul(WAD)
         .ok_or(MarketError::MathError)?
-        .checked_div(b)
+        .checked_add(b.checked_mul(ln_wad(sum)?).ok_or(MarketError::MathError)?)
         .ok_or(MarketError::MathError)?;
 
-    let exp_yes = exp_approx(x_yes)?;
-    let exp_no = exp_approx(x_no)?;
-
-    let sum = exp_yes.checked_add(exp_no).ok_or(MarketError::MathError)?;
-
-    let ln_sum = ln_approx(sum)?;
+    Ok(cost)
+}
 
-    let cost = b
-        .checked_mul(ln_sum)
+// exp((q_i - m) / b) scaled by WAD
+fn exp_scaled(q_i: u128, m: u128, b: u128) -> Result<u128> {
+    let x = m
+        .checked_sub(q_i)
         .ok_or(MarketError::MathError)?
-        .checked_div(FP_SCALE)
+        .checked_mul(WAD)
+        .ok_or(MarketError::MathError)?
+        .checked_div(b)
         .ok_or(MarketError::MathError)?;
-
-    Ok(cost)
+    exp_neg_wad(x)
 }
 
-pub fn exp_approx(x_fp: u128) -> Result<u128> {
-    const XMAX_FP: u128 = 20u128 * FP_SCALE;
-
-    let x = if x_fp > XMAX_FP { XMAX_FP } else { x_fp };
-
-    let one = FP_SCALE;
-    let x1 = x;
+// at least WAD, the largest outcome always contributes exp(0)
+fn sum_exp(q: &[u128], m: u128, b: u128) -> Result<u128> {
+    q.iter().try_fold(0u128, |sum, q_i| {
+        sum.checked_add(exp_scaled(*q_i, m, b)?)
+            .ok_or(error!(MarketError::MathError))
+    })
+}
 
-    let x2 = x1
-        .checked_mul(x1)
-        .ok_or(MarketError::MathError)?
-        .checked_div(FP_SCALE)
-        .ok_or(MarketError::MathError)?;
-    let x2div2 = x2.checked_div(2).ok_or(MarketError::MathError)?;
+// exp(-x) for x >= 0, both scaled by WAD: x = k * ln 2 + r with r in [0, ln 2), so
+// exp(-x) = exp(-r) / 2^k and the series only ever sees small arguments
+pub fn exp_neg_wad(x: u128) -> Result<u128> {
+    let k = x / LN_2_WAD;
+    if k >= 128 {
+        // below one WAD unit, not a clamp
+        return Ok(0);
+    }
+    let r = x - k * LN_2_WAD;
+
+    // exp(r) by its Taylor series, every term positive and r < 1 so it converges quickly
+    let mut term = WAD;
+    let mut exp_r = WAD;
+    let mut i = 1u128;
+    while term > 0 {
+        term = term
+            .checked_mul(r)
+            .ok_or(MarketError::MathError)?
+            .checked_div(WAD * i)
+            .ok_or(MarketError::MathError)?;
+        exp_r = exp_r.checked_add(term).ok_or(MarketError::MathError)?;
+        i += 1;
+    }
 
-    let x3 = x2
-        .checked_mul(x1)
-        .ok_or(MarketError::MathError)?
-        .checked_div(FP_SCALE)
+    let exp_neg_r = (WAD * WAD)
+        .checked_div(exp_r)
         .ok_or(MarketError::MathError)?;
-    let x3div6 = x3.checked_div(6).ok_or(MarketError::MathError)?;
+    Ok(exp_neg_r >> k)
+}
 
-    let x4 = x3
-        .checked_mul(x1)
-        .ok_or(MarketError::MathError)?
-        .checked_div(FP_SCALE)
-        .ok_or(MarketError::MathError)?;
-    let x4div24 = x4.checked_div(24).ok_or(MarketError::MathError)?;
+// ln(y) for y >= 1, both scaled by WAD: y = 2^k * z with z in [1, 2), so
+// ln(y) = k * ln 2 + 2 * atanh((z - 1) / (z + 1)) and the series argument stays below 1/3
+pub fn ln_wad(y: u128) -> Result<u128> {
+    require!(y >= WAD, MarketError::MathError);
+    let k = 127 - (y / WAD).leading_zeros();
+    let z = y >> k;
 
-    let x5 = x4
-        .checked_mul(x1)
+    let u = (z - WAD)
+        .checked_mul(WAD)
         .ok_or(MarketError::MathError)?
-        .checked_div(FP_SCALE)
+        .checked_div(z + WAD)
         .ok_or(MarketError::MathError)?;
-    let x5div120 = x5.checked_div(120).ok_or(MarketError::MathError)?;
-
-    let mut sum = one;
-    sum = sum.checked_add(x1).ok_or(MarketError::MathError)?;
-    sum = sum.checked_add(x2div2).ok_or(MarketError::MathError)?;
-    sum = sum.checked_add(x3div6).ok_or(MarketError::MathError)?;
-    sum = sum.checked_add(x4div24).ok_or(MarketError::MathError)?;
-    sum = sum.checked_add(x5div120).ok_or(MarketError::MathError)?;
-
-    Ok(sum)
-}
+    let u2 = u.checked_mul(u).ok_or(MarketError::MathError)? / WAD;
 
-pub fn ln_approx(y_fp: u128) -> Result<u128> {
-    if y_fp < FP_SCALE {
-        return err!(MarketError::MathError);
+    let mut term = u;
+    let mut series = u;
+    let mut i = 3u128;
+    while term > 0 {
+        term = term.checked_mul(u2).ok_or(MarketError::MathError)? / WAD;
+        series = series.checked_add(term / i).ok_or(MarketError::MathError)?;
+        i += 2;
     }
 
-    let numerator = y_fp.checked_sub(FP_SCALE).ok_or(MarketError::MathError)?;
-    let denominator = y_fp.checked_sub(FP_SCALE).ok_or(MarketError::MathError)?;
-
-    let z_fp = numerator
-        .checked_mul(FP_SCALE)
+    let ln = (k as u128)
+        .checked_mul(LN_2_WAD)
         .ok_or(MarketError::MathError)?
-        .checked_div(denominator)
+        .checked_add(series.checked_mul(2).ok_or(MarketError::MathError)?)
         .ok_or(MarketError::MathError)?;
+    Ok(ln)
+}
 
-    let z1 = z_fp;
-    let z2 = z1
-        .checked_mul(z1)
-        .ok_or(MarketError::MathError)?
-        .checked_div(FP_SCALE)
-        .ok_or(MarketError::MathError)?;
-    let z3 = z2
-        .checked_mul(z1)
-        .ok_or(MarketError::MathError)?
-        .checked_div(FP_SCALE)
-        .ok_or(MarketError::MathError)?;
-    let z5 = z3
-        .checked_mul(z2)
+// the AMM can lose at most b * ln(n) over the life of the market, so the subsidy deposited at
+// creation funds exactly that much liquidity
+pub fn liquidity_for_subsidy(subsidy: u64, num_outcomes: u8) -> Result<u64> {
+    require!(num_outcomes >= 2, MarketError::InvalidArgument);
+    let ln_n = ln_wad(
+        (num_outcomes as u128)
+            .checked_mul(WAD)
+            .ok_or(MarketError::MathError)?,
+    )?;
+    let b = (subsidy as u128)
+        .checked_mul(WAD)
         .ok_or(MarketError::MathError)?
-        .checked_div(FP_SCALE)
-        .ok_or(MarketError::MathError)?;
-
-    let mut sum = z1;
-    sum = sum
-        .checked_add(z3.checked_div(3).ok_or(MarketError::MathError)?)
-        .ok_or(MarketError::MathError)?;
-    sum = sum
-        .checked_add(z5.checked_div(5).ok_or(MarketError::MathError)?)
+        .checked_div(ln_n)
         .ok_or(MarketError::MathError)?;
-
-    let ln_fp = sum.checked_mul(2).ok_or(MarketError::MathError)?;
-    Ok(ln_fp)
+    u64::try_from(b).map_err(|_| error!(MarketError::MathError))
 }
 
-pub fn amm_execute_buy(
-    market_q_yes: &mut u128,
-    market_q_no: &mut u128,
-    b_fp: u128,
-    outcome: u8,
-    quantity: u128,
-) -> Result<u128> {
+pub fn amm_execute_buy(q: &mut [u128], b: u128, outcome: u8, quantity: u128) -> Result<u128> {
     if quantity == 0 {
         return Ok(0);
     }
 
-    let cost_fp: u128 = if outcome == OutcomeSide::Yes as u8 {
-        amm_cost_to_yes(*market_q_yes, *market_q_no, b_fp, quantity)?
-    } else {
-        amm_cost_to_no(*market_q_yes, *market_q_no, b_fp, quantity)?
-    };
+    let cost = amm_cost_to_buy(q, b, outcome, quantity)?;
 
-    if outcome == OutcomeSide::Yes as u8 {
-        *market_q_yes = market_q_yes
-            .checked_add(quantity)
-            .ok_or(MarketError::MathError)?;
-    } else {
-        *market_q_no = market_q_no
-            .checked_add(quantity)
-            .ok_or(MarketError::MathError)?;
-    }
+    let q_outcome = &mut q[outcome as usize];
+    *q_outcome = q_outcome
+        .checked_add(quantity)
+        .ok_or(MarketError::MathError)?;
 
-    Ok(cost_fp)
+    Ok(cost)
 }
 
-pub fn amm_price_per_token(q_yes: u128, q_no: u128, b: u128, outcome: u8) -> Result<u128> {
-    if b == 0 {
-        return err!(MarketError::MathError);
-    }
-
-    let x_yes = q_yes
-        .checked_mul(FP_SCALE)
-        .ok_or(MarketError::MathError)?
-        .checked_div(b)
-        .ok_or(MarketError::MathError)?;
-    let x_no = q_no
+// marginal price of an outcome, exp(q_i / b) / sum_j exp(q_j / b), scaled by FP_SCALE
+pub fn amm_price_per_token(q: &[u128], b: u128, outcome: u8) -> Result<u128> {
+    require!(b > 0, MarketError::MathError);
+    let q_outcome = *q.get(outcome as usize).ok_or(MarketError::InvalidOutcome)?;
+    let m = q
+        .iter()
+        .copied()
+        .max()
+        .ok_or(MarketError::InvalidArgument)?;
+
+    let sum = sum_exp(q, m, b)?;
+    let price_per_token = exp_scaled(q_outcome, m, b)?
         .checked_mul(FP_SCALE)
         .ok_or(MarketError::MathError)?
-        .checked_div(b)
+        .checked_div(sum)
         .ok_or(MarketError::MathError)?;
 
-    let exp_x = exp_approx(x_yes)?;
-    let exp_y = exp_approx(x_no)?;
-
-    let sum = exp_x.checked_add(exp_y).ok_or(MarketError::MathError)?;
+    Ok(price_per_token)
+}
 
-    if sum == 0 {
-        return err!(MarketError::MathError);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNIT: u128 = 1_000_000;
+
+    #[test]
+    fn exp_and_ln_match_known_values() {
+        // exp(-1) = 0.367879441171442321...
+        let e_inv = exp_neg_wad(WAD).unwrap();
+        assert!(e_inv.abs_diff(367_879_441_171_442_321) < 1_000);
+        assert_eq!(exp_neg_wad(0).unwrap(), WAD);
+        assert_eq!(exp_neg_wad(200 * WAD).unwrap(), 0);
+
+        // ln(2), ln(10) = 2.302585092994045684...
+        assert!(ln_wad(2 * WAD).unwrap().abs_diff(LN_2_WAD) < 1_000);
+        assert!(
+            ln_wad(10 * WAD)
+                .unwrap()
+                .abs_diff(2_302_585_092_994_045_684)
+                < 1_000
+        );
+        assert_eq!(ln_wad(WAD).unwrap(), 0);
+        assert!(ln_wad(WAD - 1).is_err());
     }
 
-    let prob_x = exp_x
-        .checked_mul(FP_SCALE)
-        .ok_or(MarketError::MathError)?
-        .checked_div(sum)
-        .ok_or(MarketError::MathError)?;
-    let prob_y = FP_SCALE.checked_sub(prob_x).ok_or(MarketError::MathError)?;
+    #[test]
+    fn buys_cost_a_positive_rising_amount() {
+        // a 1_000 USDC subsidy on a binary market
+        let b = liquidity_for_subsidy(1_000 * UNIT as u64, 2).unwrap() as u128;
+        let mut q = [0u128; 2];
+        let mut last = 0;
+        for _ in 0..50 {
+            let cost = amm_execute_buy(&mut q, b, 0, 100 * UNIT).unwrap();
+            assert!(cost > last, "cost {} after {}", cost, last);
+            // never more than the tokens pay out
+            assert!(cost <= 100 * UNIT);
+            last = cost;
+        }
+
+        // the first buys sit just above the 0.5 starting price
+        let first = amm_cost_to_buy(&[0, 0], b, 1, UNIT).unwrap();
+        assert!(first > UNIT / 2 && first < UNIT / 2 + 1_000);
+    }
 
-    let price_per_token = if outcome == OutcomeSide::Yes as u8 {
-        prob_x
-    } else {
-        prob_y
-    };
+    #[test]
+    fn loss_is_bounded_by_the_subsidy() {
+        for n in [2usize, 3, 8, MAX_OUTCOMES] {
+            let subsidy = 500 * UNIT;
+            let b = liquidity_for_subsidy(subsidy as u64, n as u8).unwrap() as u128;
+            let q = vec![0u128; n];
+            for quantity in [UNIT, 100 * UNIT, 10_000 * UNIT, 1_000_000 * UNIT] {
+                let cost = amm_cost_to_buy(&q, b, 0, quantity).unwrap();
+                assert!(cost > 0);
+                // every token of the bought outcome pays 1, the AMM lost at most b * ln(n)
+                assert!(quantity - cost.min(quantity) <= subsidy);
+            }
+        }
+    }
 
-    Ok(price_per_token)
+    #[test]
+    fn prices_sum_to_one() {
+        let b = liquidity_for_subsidy(1_000 * UNIT as u64, 3).unwrap() as u128;
+        let q = [0, 700 * UNIT, 2_000 * UNIT];
+        let total: u128 = (0..3)
+            .map(|outcome| amm_price_per_token(&q, b, outcome).unwrap())
+            .sum();
+        assert!(total.abs_diff(FP_SCALE) <= 3);
+    }
 }
@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
+
+use crate::{
+    constants::{BOND_VAULT_SEED, MARKET_SEED, PROPOSAL_SEED, VAULT_USDC_SEED},
+    error::MarketError,
+    events::{BondSettled, MarketResolved},
+    state::{Market, MarketStatus, ResolutionProposal},
+    utils::debug_check_solvency,
+};
+
+#[derive(Accounts)]
+pub struct ArbitrateDispute<'info> {
+    pub oracle: Signer<'info>,
+
+    #[account(mut , has_one = oracle @ MarketError::Unauthorized)]
+    pub market: Account<'info, Market>,
+
+    #[account(seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
+    pub vault_usdc: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED , market.key().as_ref()],
+        bump = proposal.bump,
+        has_one = market @ MarketError::InvalidArgument
+    )]
+    pub proposal: Account<'info, ResolutionProposal>,
+
+    #[account(mut , seeds = [BOND_VAULT_SEED , market.key().as_ref()] , bump)]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(mut , token::authority = proposal.proposer)]
+    pub proposer_usdc: Account<'info, TokenAccount>,
+
+    #[account(mut , token::authority = proposal.disputer)]
+    pub disputer_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn arbitrate_dispute_handler(ctx: Context<ArbitrateDispute>, outcome: u8) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    require!(
+        ctx.accounts.market.status == MarketStatus::Proposed,
+        MarketError::InvalidMarketStatus
+    );
+    require!(proposal.is_disputed(), MarketError::NotDisputed);
+
+    // the loser's bond is slashed to whichever side the arbiter agrees with, when the ruling is
+    // neither side's outcome nobody argued for it and both bonds go back
+    let (proposer_payout, disputer_payout) = if outcome == proposal.proposed_outcome {
+        (
+            proposal.bond.checked_mul(2).ok_or(MarketError::MathError)?,
+            0,
+        )
+    } else if outcome == proposal.disputed_outcome {
+        (
+            0,
+            proposal.bond.checked_mul(2).ok_or(MarketError::MathError)?,
+        )
+    } else {
+        (proposal.bond, proposal.bond)
+    };
+
+    let bump = ctx.accounts.market.bump;
+    let seeds: &[&[&[u8]]] = &[&[
+        MARKET_SEED,
+        &ctx.accounts.market.market_id.to_le_bytes(),
+        &[bump],
+    ]];
+
+    let market = &mut ctx.accounts.market;
+    for (destination, recipient, payout) in [
+        (
+            &ctx.accounts.proposer_usdc,
+            proposal.proposer,
+            proposer_payout,
+        ),
+        (
+            &ctx.accounts.disputer_usdc,
+            proposal.disputer,
+            disputer_payout,
+        ),
+    ] {
+        if payout == 0 {
+            continue;
+        }
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.bond_vault.to_account_info(),
+                to: destination.to_account_info(),
+                authority: market.to_account_info(),
+            },
+            seeds,
+        );
+        transfer(cpi_ctx, payout)?;
+
+        emit!(BondSettled {
+            seq: market.next_event_seq()?,
+            market: market.key(),
+            recipient,
+            amount: payout,
+        });
+    }
+
+    proposal.settled = true;
+
+    market.apply_resolution(outcome, Clock::get()?.unix_timestamp)?;
+
+    emit!(MarketResolved {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        resolver: ctx.accounts.oracle.key(),
+        status: market.status,
+        payout_numerators: market.payout_numerators.to_vec(),
+        payout_denominator: market.payout_denominator,
+        claimable_at: market.claims_open_at()?,
+    });
+
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
+
+    Ok(())
+}
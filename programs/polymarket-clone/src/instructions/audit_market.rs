@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+use crate::{constants::VAULT_USDC_SEED, error::MarketError, state::Market, utils::audit_solvency};
+
+// permissionless: checks the tracked supplies against the live mints and recomputes the solvency
+// invariant against the vault balance
+#[derive(Accounts)]
+pub struct AuditMarket<'info> {
+    #[account(mut , has_one = vault_usdc @ MarketError::InvalidArgument)]
+    pub market: Account<'info, Market>,
+
+    #[account(seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
+    pub vault_usdc: Account<'info, TokenAccount>,
+}
+
+// remaining accounts: every outcome mint in outcome order
+pub fn audit_market_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, AuditMarket<'info>>,
+) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let n_outcomes = market.num_outcomes as usize;
+    require!(
+        ctx.remaining_accounts.len() == n_outcomes,
+        MarketError::InvalidArgument
+    );
+
+    let mut supplies = Vec::with_capacity(n_outcomes);
+    for (outcome, mint_info) in ctx.remaining_accounts.iter().enumerate() {
+        require!(
+            market.outcome_mints[outcome] == mint_info.key(),
+            MarketError::InvalidOutcome
+        );
+        let mint: Account<Mint> = Account::try_from(mint_info)?;
+        // holders may burn their own tokens, which only leaves the tracked supply above the live
+        // one; a live supply above it means tokens were minted outside the market's accounting
+        if mint.supply > market.outcome_supply[outcome] {
+            msg!(
+                "outcome {} mint supply {} > tracked supply {}",
+                outcome,
+                mint.supply,
+                market.outcome_supply[outcome]
+            );
+            return err!(MarketError::SupplyMismatch);
+        }
+        supplies.push(market.outcome_supply[outcome]);
+    }
+
+    audit_solvency(market, ctx.accounts.vault_usdc.amount, &supplies)
+}
@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+
+use crate::{
+    constants::{BOND_VAULT_SEED, MARKET_SEED, PROPOSAL_SEED, VAULT_USDC_SEED},
+    error::MarketError,
+    events::{BondSettled, MarketResolved},
+    state::{Market, MarketStatus, RefundMode, ResolutionProposal},
+    utils::debug_check_solvency,
+};
+
+// permissionless fallback for markets nobody resolved: after resolution_deadline anyone can cancel,
+// holders then redeem at the refund rate fixed here and resting orders are released through the
+// usual cancel_order_request / match_request / consume_events path
+#[derive(Accounts)]
+pub struct CancelMarket<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
+    pub vault_usdc: Account<'info, TokenAccount>,
+
+    // only for a disputed proposal the arbiter never ruled on, whose bonds both go back
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED , market.key().as_ref()],
+        bump = proposal.bump,
+        has_one = market @ MarketError::InvalidArgument
+    )]
+    pub proposal: Option<Account<'info, ResolutionProposal>>,
+
+    #[account(mut , seeds = [BOND_VAULT_SEED , market.key().as_ref()] , bump)]
+    pub bond_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub proposer_usdc: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub disputer_usdc: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+// remaining accounts: every outcome mint in outcome order, only read for RefundMode::NetDeposits
+pub fn cancel_market_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CancelMarket<'info>>,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let market = &mut ctx.accounts.market;
+    market.refresh_status(now);
+    // an undisputed proposal is still settled through finalize_resolution, a disputed one only
+    // through arbitrate_dispute until the deadline passes
+    let disputed = match market.status {
+        MarketStatus::Pending | MarketStatus::Closed => false,
+        MarketStatus::Proposed => true,
+        _ => return err!(MarketError::InvalidMarketStatus),
+    };
+    require!(
+        now >= market.resolution_deadline,
+        MarketError::ResolutionDeadlineNotReached
+    );
+    if disputed {
+        refund_bonds(ctx.accounts)?;
+    }
+    let market = &mut ctx.accounts.market;
+
+    let mut supplies = Vec::with_capacity(market.num_outcomes as usize);
+    if market.refund_mode == RefundMode::NetDeposits {
+        require!(
+            ctx.remaining_accounts.len() == market.num_outcomes as usize,
+            MarketError::InvalidArgument
+        );
+        for (outcome, mint_info) in ctx.remaining_accounts.iter().enumerate() {
+            require!(
+                market.outcome_mints[outcome] == mint_info.key(),
+                MarketError::InvalidOutcome
+            );
+            let mint: Account<Mint> = Account::try_from(mint_info)?;
+            supplies.push(mint.supply);
+        }
+    }
+
+    market.apply_cancellation(&supplies, now)?;
+
+    emit!(MarketResolved {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        resolver: ctx.accounts.caller.key(),
+        status: market.status,
+        payout_numerators: market.payout_numerators.to_vec(),
+        payout_denominator: market.payout_denominator,
+        claimable_at: market.claims_open_at()?,
+    });
+
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
+
+    Ok(())
+}
+
+// neither side of a dispute nobody ruled on was proven wrong, so each gets its bond back
+fn refund_bonds(accounts: &mut CancelMarket) -> Result<()> {
+    let (
+        Some(proposal),
+        Some(bond_vault),
+        Some(proposer_usdc),
+        Some(disputer_usdc),
+        Some(token_program),
+    ) = (
+        accounts.proposal.as_mut(),
+        accounts.bond_vault.as_ref(),
+        accounts.proposer_usdc.as_ref(),
+        accounts.disputer_usdc.as_ref(),
+        accounts.token_program.as_ref(),
+    )
+    else {
+        return err!(MarketError::InvalidArgument);
+    };
+    require!(
+        proposal.is_disputed() && !proposal.settled,
+        MarketError::NotDisputed
+    );
+    require!(
+        proposer_usdc.owner == proposal.proposer && disputer_usdc.owner == proposal.disputer,
+        MarketError::Unauthorized
+    );
+
+    let market = &mut accounts.market;
+    let bump = market.bump;
+    let market_id = market.market_id.to_le_bytes();
+    let seeds: &[&[&[u8]]] = &[&[MARKET_SEED, &market_id, &[bump]]];
+
+    for (destination, recipient) in [
+        (proposer_usdc, proposal.proposer),
+        (disputer_usdc, proposal.disputer),
+    ] {
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: bond_vault.to_account_info(),
+                to: destination.to_account_info(),
+                authority: market.to_account_info(),
+            },
+            seeds,
+        );
+        transfer(cpi_ctx, proposal.bond)?;
+
+        emit!(BondSettled {
+            seq: market.next_event_seq()?,
+            market: market.key(),
+            recipient,
+            amount: proposal.bond,
+        });
+    }
+
+    proposal.settled = true;
+    Ok(())
+}
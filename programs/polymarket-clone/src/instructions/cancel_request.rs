@@ -1,9 +1,11 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
 
 use crate::{
-    constants::REQUEST_QUEUE_SEED,
-    state::{Market, Request, RequestQueue, RequestType},
-    utils::enqueue_request,
+    constants::{OPEN_ORDER_SEED, REQUEST_QUEUE_SEED, VAULT_USDC_SEED},
+    error::MarketError,
+    state::{Market, OpenOrder, Request, RequestQueue, RequestType},
+    utils::{debug_check_solvency, enqueue_request, find_order_slot},
 };
 
 #[derive(Accounts)]
@@ -13,24 +15,39 @@ pub struct CancelOrder<'info> {
     pub owner: Signer<'info>,
     #[account(mut)]
     pub market: Account<'info, Market>,
+    #[account(seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
+    pub vault_usdc: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [OPEN_ORDER_SEED , market.key().as_ref() , owner.key().as_ref()],
+        bump = open_order.bump,
+        has_one = owner @ MarketError::Unauthorized,
+        has_one = market @ MarketError::Unauthorized
+    )]
+    pub open_order: Account<'info, OpenOrder>,
     #[account(mut , seeds = [REQUEST_QUEUE_SEED, &market.market_id.to_le_bytes()] , bump)]
     pub request_queue: Account<'info, RequestQueue>,
 }
 
 pub fn cancel_order_handler(ctx: Context<CancelOrder>, order_id: u64) -> Result<()> {
+    let open_order = &ctx.accounts.open_order;
+    let owner_slot = find_order_slot(open_order, order_id).ok_or(MarketError::OrderNotFound)?;
+    let slot = open_order.slots[owner_slot as usize];
+
     let request = Request {
         request_type: RequestType::CancelOrder as u8,
         owner: ctx.accounts.owner.key(),
-        open_order: Pubkey::default(),
-        side: 0,
-        price: 0,
+        open_order: open_order.key(),
+        owner_slot,
+        side: slot.side,
+        price: slot.price as u128,
         quantity: 0,
-        order_id: order_id,
+        order_id,
         client_id: 0,
-        outcome: 0,
+        outcome: slot.outcome,
         timestamp: Clock::get()?.unix_timestamp,
     };
 
     enqueue_request(&mut ctx.accounts.request_queue, request)?;
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
     Ok(())
 }
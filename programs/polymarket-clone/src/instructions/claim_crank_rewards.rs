@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
+
+use crate::{
+    constants::{CRANKER_LEDGER_SEED, FEE_VAULT_USDC, MARKET_SEED},
+    error::MarketError,
+    state::{CrankerLedger, Market},
+};
+
+#[derive(Accounts)]
+pub struct ClaimCrankRewards<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [CRANKER_LEDGER_SEED , market.key().as_ref() , cranker.key().as_ref()],
+        bump = cranker_ledger.bump,
+        has_one = cranker @ MarketError::Unauthorized,
+        has_one = market @ MarketError::Unauthorized
+    )]
+    pub cranker_ledger: Account<'info, CrankerLedger>,
+
+    #[account(mut , seeds = [FEE_VAULT_USDC , &market.market_id.to_le_bytes()] , bump)]
+    pub fee_vault_usdc: Account<'info, TokenAccount>,
+
+    #[account(mut , constraint = cranker_usdc.mint == market.usdc_mint @ MarketError::InvalidArgument)]
+    pub cranker_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn claim_crank_rewards_handler(ctx: Context<ClaimCrankRewards>) -> Result<()> {
+    let amount = ctx.accounts.cranker_ledger.accrued;
+    if amount == 0 {
+        return Ok(());
+    }
+    require!(
+        ctx.accounts.fee_vault_usdc.amount >= amount,
+        MarketError::InsufficientBalance
+    );
+
+    let bump = ctx.accounts.market.bump;
+    let seeds: &[&[&[u8]]] = &[&[
+        MARKET_SEED,
+        &ctx.accounts.market.market_id.to_le_bytes(),
+        &[bump],
+    ]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.fee_vault_usdc.to_account_info(),
+            to: ctx.accounts.cranker_usdc.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        },
+        seeds,
+    );
+
+    transfer(cpi_ctx, amount)?;
+
+    let cranker_ledger = &mut ctx.accounts.cranker_ledger;
+    cranker_ledger.accrued = 0;
+    cranker_ledger.total_claimed = cranker_ledger
+        .total_claimed
+        .checked_add(amount)
+        .ok_or(MarketError::MathError)?;
+
+    let market = &mut ctx.accounts.market;
+    market.crank_rewards_owed = market
+        .crank_rewards_owed
+        .checked_sub(amount)
+        .ok_or(MarketError::MathError)?;
+
+    Ok(())
+}
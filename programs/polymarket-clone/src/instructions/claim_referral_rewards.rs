@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
+
+use crate::{
+    constants::{FEE_VAULT_USDC, MARKET_SEED, REFERRER_LEDGER_SEED},
+    error::MarketError,
+    state::{Market, ReferrerLedger},
+};
+
+#[derive(Accounts)]
+pub struct ClaimReferralRewards<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [REFERRER_LEDGER_SEED , market.key().as_ref() , referrer.key().as_ref()],
+        bump = referrer_ledger.bump,
+        has_one = referrer @ MarketError::Unauthorized,
+        has_one = market @ MarketError::Unauthorized
+    )]
+    pub referrer_ledger: Account<'info, ReferrerLedger>,
+
+    #[account(mut , seeds = [FEE_VAULT_USDC , &market.market_id.to_le_bytes()] , bump)]
+    pub fee_vault_usdc: Account<'info, TokenAccount>,
+
+    #[account(mut , constraint = referrer_usdc.mint == market.usdc_mint @ MarketError::InvalidArgument)]
+    pub referrer_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn claim_referral_rewards_handler(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+    let amount = ctx.accounts.referrer_ledger.accrued;
+    if amount == 0 {
+        return Ok(());
+    }
+    require!(
+        ctx.accounts.fee_vault_usdc.amount >= amount,
+        MarketError::InsufficientBalance
+    );
+
+    let bump = ctx.accounts.market.bump;
+    let seeds: &[&[&[u8]]] = &[&[
+        MARKET_SEED,
+        &ctx.accounts.market.market_id.to_le_bytes(),
+        &[bump],
+    ]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.fee_vault_usdc.to_account_info(),
+            to: ctx.accounts.referrer_usdc.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        },
+        seeds,
+    );
+
+    transfer(cpi_ctx, amount)?;
+
+    let referrer_ledger = &mut ctx.accounts.referrer_ledger;
+    referrer_ledger.accrued = 0;
+    referrer_ledger.total_claimed = referrer_ledger
+        .total_claimed
+        .checked_add(amount)
+        .ok_or(MarketError::MathError)?;
+
+    let market = &mut ctx.accounts.market;
+    market.referral_rewards_owed = market
+        .referral_rewards_owed
+        .checked_sub(amount)
+        .ok_or(MarketError::MathError)?;
+
+    Ok(())
+}
@@ -1,10 +1,14 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{burn, transfer, Burn, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token::{
+    burn, close_account, transfer, Burn, CloseAccount, Token, TokenAccount, Transfer,
+};
 
 use crate::{
-    constants::MARKET_SEED,
+    constants::{MARKET_SEED, OPEN_ORDER_SEED, VAULT_USDC_SEED},
     error::MarketError,
-    state::{Market, MarketStatus},
+    events::RewardClaimed,
+    state::{Market, OpenOrder},
+    utils::debug_check_solvency,
 };
 
 #[derive(Accounts)]
@@ -16,55 +20,147 @@ pub struct ClaimReward<'info> {
     #[account(mut)]
     pub market: Account<'info, Market>,
 
-    #[account(mut)]
-    pub claimer_outcome_account: Account<'info, TokenAccount>,
-
-    #[account(mut)]
+    #[account(
+        mut,
+        token::mint = market.usdc_mint,
+        token::authority = claimer
+    )]
     pub claimer_usdc_account: Account<'info, TokenAccount>,
 
-    #[account(mut)]
+    #[account(mut , seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
     pub vault_usdc: Account<'info, TokenAccount>,
 
-    pub outcome_mint: Account<'info, Mint>,
+    // only needed when the caller wants it closed along with the emptied token accounts
+    #[account(
+        mut,
+        seeds = [OPEN_ORDER_SEED , market.key().as_ref() , claimer.key().as_ref()],
+        bump = open_order.bump,
+        has_one = market @ MarketError::Unauthorized
+    )]
+    pub open_order: Option<Account<'info, OpenOrder>>,
 
     token_program: Program<'info, Token>,
 }
 
-pub fn claim_reward_handler(ctx: Context<ClaimReward>, amount: u64) -> Result<()> {
-    match ctx.accounts.market.status {
-        MarketStatus::ResolvedYes | MarketStatus::ResolvedNo => {}
-        _ => return err!(MarketError::MarketNotOpen),
-    }
+#[repr(C)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 
-    let cpi_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        Burn {
-            mint: ctx.accounts.outcome_mint.to_account_info(),
-            from: ctx.accounts.claimer_outcome_account.to_account_info(),
-            authority: ctx.accounts.claimer.to_account_info(),
-        },
-    );
+pub struct ClaimRewardParams {
+    pub close_accounts: bool,
+}
 
-    burn(cpi_ctx, amount)?;
-
-    let bump = ctx.accounts.market.bump;
-    let signer_seeds: &[&[&[u8]]] = &[&[
-        MARKET_SEED,
-        &ctx.accounts.market.market_id.to_le_bytes(),
-        &[bump],
-    ]];
-
-    let cpi_ctx_2 = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.vault_usdc.to_account_info(),
-            to: ctx.accounts.claimer_usdc_account.to_account_info(),
-            authority: ctx.accounts.market.to_account_info(),
-        },
-        signer_seeds,
+// remaining accounts: (outcome_mint, claimer_outcome_account) for each outcome being redeemed,
+// the whole balance is burned and losing outcomes simply pay nothing
+pub fn claim_reward_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimReward<'info>>,
+    params: ClaimRewardParams,
+) -> Result<()> {
+    ctx.accounts
+        .market
+        .require_claimable(Clock::get()?.unix_timestamp)?;
+    require!(
+        !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len() % 2 == 0,
+        MarketError::InvalidArgument
     );
 
-    transfer(cpi_ctx_2, amount)?;
+    let claimer = ctx.accounts.claimer.key();
+    let mut total_payout = 0u64;
+
+    for accounts in ctx.remaining_accounts.chunks(2) {
+        let (outcome_mint, claimer_outcome_info) = (&accounts[0], &accounts[1]);
+        let outcome_index = ctx.accounts.market.outcome_index(&outcome_mint.key())?;
+
+        let claimer_outcome: Account<TokenAccount> = Account::try_from(claimer_outcome_info)?;
+        require!(
+            claimer_outcome.mint == outcome_mint.key(),
+            MarketError::InvalidOutcome
+        );
+        require!(claimer_outcome.owner == claimer, MarketError::Unauthorized);
+
+        let amount = claimer_outcome.amount;
+        let payout = ctx.accounts.market.payout_for(outcome_index, amount)?;
+
+        if amount > 0 {
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: outcome_mint.clone(),
+                    from: claimer_outcome_info.clone(),
+                    authority: ctx.accounts.claimer.to_account_info(),
+                },
+            );
+
+            burn(cpi_ctx, amount)?;
+            ctx.accounts
+                .market
+                .record_burn(outcome_index as u8, amount)?;
+        }
+
+        if params.close_accounts {
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: claimer_outcome_info.clone(),
+                    destination: ctx.accounts.claimer.to_account_info(),
+                    authority: ctx.accounts.claimer.to_account_info(),
+                },
+            );
+
+            close_account(cpi_ctx)?;
+        }
+
+        total_payout = total_payout
+            .checked_add(payout)
+            .ok_or(MarketError::MathError)?;
+
+        let market = &mut ctx.accounts.market;
+        emit!(RewardClaimed {
+            seq: market.next_event_seq()?,
+            market: market.key(),
+            claimer,
+            outcome_mint: outcome_mint.key(),
+            amount,
+            payout,
+        });
+    }
+
+    if total_payout > 0 {
+        let bump = ctx.accounts.market.bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            MARKET_SEED,
+            &ctx.accounts.market.market_id.to_le_bytes(),
+            &[bump],
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_usdc.to_account_info(),
+                to: ctx.accounts.claimer_usdc_account.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            },
+            signer_seeds,
+        );
+
+        transfer(cpi_ctx, total_payout)?;
+    }
+
+    if params.close_accounts {
+        if let Some(open_order) = &ctx.accounts.open_order {
+            // anything still held by the OpenOrder has to be settled or cancelled first
+            require!(
+                open_order.free_base.iter().all(|free| *free == 0)
+                    && open_order.free_quote == 0
+                    && open_order.locked_base == 0
+                    && open_order.locked_quote == 0
+                    && open_order.slots_bitmaps == 0,
+                MarketError::OpenOrderNotEmpty
+            );
+            open_order.close(ctx.accounts.claimer.to_account_info())?;
+        }
+    }
+
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
 
     Ok(())
 }
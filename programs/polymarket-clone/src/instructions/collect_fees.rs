@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
+
+use crate::{
+    constants::{FEE_VAULT_USDC, GLOBAL_CONFIG_SEED, MARKET_SEED},
+    error::MarketError,
+    events::FeesCollected,
+    state::{GlobalConfig, Market},
+};
+
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    pub fee_authority: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        has_one = fee_authority @ MarketError::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(mut , seeds = [FEE_VAULT_USDC , &market.market_id.to_le_bytes()] , bump)]
+    pub fee_vault_usdc: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = market.usdc_mint,
+        token::authority = global_config.treasury
+    )]
+    pub treasury_usdc: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = market.usdc_mint,
+        token::authority = market.creator
+    )]
+    pub creator_usdc: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = market.usdc_mint,
+        token::authority = global_config.insurance_fund
+    )]
+    pub insurance_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn collect_fees_handler(ctx: Context<CollectFees>) -> Result<()> {
+    let amount = ctx
+        .accounts
+        .market
+        .collectable_fees(ctx.accounts.fee_vault_usdc.amount);
+    if amount == 0 {
+        return Ok(());
+    }
+    let (protocol_amount, creator_amount, insurance_amount) =
+        ctx.accounts.global_config.split_fees(amount)?;
+
+    let bump = ctx.accounts.market.bump;
+    let seeds: &[&[&[u8]]] = &[&[
+        MARKET_SEED,
+        &ctx.accounts.market.market_id.to_le_bytes(),
+        &[bump],
+    ]];
+
+    for (destination, share) in [
+        (
+            ctx.accounts.treasury_usdc.to_account_info(),
+            protocol_amount,
+        ),
+        (ctx.accounts.creator_usdc.to_account_info(), creator_amount),
+        (
+            ctx.accounts.insurance_usdc.to_account_info(),
+            insurance_amount,
+        ),
+    ] {
+        if share == 0 {
+            continue;
+        }
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.fee_vault_usdc.to_account_info(),
+                to: destination,
+                authority: ctx.accounts.market.to_account_info(),
+            },
+            seeds,
+        );
+        transfer(cpi_ctx, share)?;
+    }
+
+    let market = &mut ctx.accounts.market;
+    emit!(FeesCollected {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        protocol_amount,
+        creator_amount,
+        insurance_amount,
+    });
+
+    Ok(())
+}
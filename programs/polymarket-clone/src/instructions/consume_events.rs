@@ -1,14 +1,21 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{mint_to, transfer, Mint, MintTo, Token, TokenAccount, Transfer};
+use anchor_spl::token::{mint_to, transfer, MintTo, Token, TokenAccount, Transfer};
+use solana_program::compute_units::sol_remaining_compute_units;
 
 use crate::{
     constants::{
-        EVENT_QUEUE_SEED, FEE_VAULT_USDC, MARKET_SEED, MAX_EVENTS, PRICE_PRECISION_SCALE,
-        VAULT_NO_SEED, VAULT_USDC_SEED, VAULT_YES_SEED,
+        CONSUME_EVENT_CU_RESERVE, CRANKER_LEDGER_SEED, EVENT_QUEUE_SEED, FEE_VAULT_USDC,
+        GLOBAL_CONFIG_SEED, MARKET_SEED, MAX_EVENTS, PRICE_PRECISION_SCALE, TRADER_STATS_SEED,
+        VAULT_USDC_SEED,
     },
     error::MarketError,
-    instructions::{amm_execute_buy, FP_SCALE},
-    state::{EventQueue, EventType, Market, OpenOrder, OrderSide, OutcomeSide},
+    events::{CrankProgress, CrankType, OrderCancelled, OrderFilled},
+    instructions::amm_execute_buy,
+    state::{
+        CrankerLedger, EventQueue, EventType, GlobalConfig, Market, OpenOrder, OrderSide,
+        ReferrerLedger, TraderStats,
+    },
+    utils::{debug_check_solvency, fill_order_slot, release_order_slot},
 };
 
 #[derive(Accounts)]
@@ -26,17 +33,18 @@ pub struct ConsumeEvents<'info> {
     #[account(mut, seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
     pub vault_usdc: Account<'info, TokenAccount>,
 
-    #[account(mut , seeds = [VAULT_YES_SEED , &market.market_id.to_le_bytes()] , bump)]
-    pub vault_yes: Account<'info, TokenAccount>,
-
-    #[account(mut , seeds = [VAULT_NO_SEED , &market.market_id.to_le_bytes()] , bump)]
-    pub vault_no: Account<'info, TokenAccount>,
-
     #[account(mut , seeds = [FEE_VAULT_USDC , &market.market_id.to_le_bytes()] , bump)]
     pub fee_vault_usdc: Account<'info, TokenAccount>,
 
-    pub yes_mint: Account<'info, Mint>,
-    pub no_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [CRANKER_LEDGER_SEED , market.key().as_ref() , cranker.key().as_ref()],
+        bump = cranker_ledger.bump
+    )]
+    pub cranker_ledger: Account<'info, CrankerLedger>,
+
+    #[account(seeds = [GLOBAL_CONFIG_SEED] , bump = global_config.bump)]
+    pub global_config: Account<'info, GlobalConfig>,
 
     pub token_program: Program<'info, Token>,
 }
@@ -44,18 +52,29 @@ pub struct ConsumeEvents<'info> {
 pub fn consume_events_handler<'info>(
     ctx: Context<'_, '_, 'info, 'info, ConsumeEvents<'info>>,
     max_events: u16,
-) -> Result<()> {
+) -> Result<CrankProgress> {
     let event_queue = &mut ctx.accounts.event_queue;
     let market = &mut ctx.accounts.market;
 
     let n_events = core::cmp::min(event_queue.count as usize, max_events as usize);
 
     if n_events == 0 {
-        return Ok(());
+        let progress = CrankProgress {
+            market: market.key(),
+            crank_type: CrankType::ConsumeEvents,
+            processed: 0,
+            remaining: event_queue.count,
+            compute_exhausted: false,
+        };
+        emit!(progress);
+        return Ok(progress);
     }
 
     let remaining_accounts = &ctx.remaining_accounts;
-    let expected_length = 6 * n_events + 1;
+    // per event: maker_oo, maker_outcome, maker_usdc, taker_oo, taker_usdc, taker_outcome,
+    // outcome_vault, outcome_mint (both for the event's outcome), taker_stats, referrer_ledger
+    // (of the taker's referrer, any account when it has none)
+    let expected_length = 10 * n_events;
     require!(
         remaining_accounts.len() >= expected_length,
         MarketError::NoMatchingOrder
@@ -65,10 +84,18 @@ pub fn consume_events_handler<'info>(
 
     let mut remaining_index = 0usize;
 
-    let cranker_usdc_account: &AccountInfo<'info> = &remaining_accounts[expected_length - 1];
+    let mut processed_events = 0usize;
+    let mut compute_exhausted = false;
+    let mut accrued_reward = 0u64;
+    let now = Clock::get()?.unix_timestamp;
 
     for i in 0..n_events {
-        let event_index = ((event_queue.head as usize + i) as usize) % MAX_EVENTS;
+        if sol_remaining_compute_units() < CONSUME_EVENT_CU_RESERVE {
+            compute_exhausted = true;
+            break;
+        }
+
+        let event_index = (event_queue.head as usize + i) % MAX_EVENTS;
         let event = event_queue.events[event_index];
 
         let maker_oo_info = &remaining_accounts[remaining_index];
@@ -79,130 +106,117 @@ pub fn consume_events_handler<'info>(
         remaining_index += 1;
         let taker_oo_info = &remaining_accounts[remaining_index];
         remaining_index += 1;
-        let _taker_usdc_info = &remaining_accounts[remaining_index];
+        let taker_usdc_info = &remaining_accounts[remaining_index];
         remaining_index += 1;
         let taker_outcome_info = &remaining_accounts[remaining_index];
         remaining_index += 1;
+        let outcome_vault_info = &remaining_accounts[remaining_index];
+        remaining_index += 1;
+        let outcome_mint_info = &remaining_accounts[remaining_index];
+        remaining_index += 1;
+        let taker_stats_info = &remaining_accounts[remaining_index];
+        remaining_index += 1;
+        let referrer_ledger_info = &remaining_accounts[remaining_index];
+        remaining_index += 1;
 
-        let mut maker_oo: Account<OpenOrder> = Account::try_from(&maker_oo_info)?;
-        let mut taker_oo: Account<OpenOrder> = Account::try_from(&taker_oo_info)?;
-
+        market.check_outcome_vault(event.outcome, outcome_vault_info.key)?;
         require!(
-            maker_oo.key() == event.makers_open_orders || maker_oo.key() == market.key(),
-            MarketError::NoMatchingOrder
-        );
-        require!(
-            taker_oo.key() == event.taker_open_orders,
-            MarketError::NoMatchingOrder
+            market.outcome_mints[event.outcome as usize] == outcome_mint_info.key(),
+            MarketError::InvalidOutcome
         );
 
-        let maker_usdc: Account<TokenAccount> = Account::try_from(&maker_usdc_info)?;
+        let maker_usdc: Account<TokenAccount> = Account::try_from(maker_usdc_info)?;
         let maker_outcome: Account<TokenAccount> = Account::try_from(maker_outcome_info)?;
-        let taker_outcome: Account<TokenAccount> = Account::try_from(&taker_outcome_info)?;
-        //let taker_usdc: Account<TokenAccount> = Account::try_from(taker_usdc_info)?;
-
-        let usdc_amount = (event.price)
-            .checked_mul(event.quantity as u128)
-            .ok_or(MarketError::MathError)?
-            .checked_div(PRICE_PRECISION_SCALE)
-            .ok_or(MarketError::MathError)? as u64;
-
-        let fee = (usdc_amount as u128)
-            .checked_mul(market.fee_bps as u128)
-            .ok_or(MarketError::MathError)?
-            .checked_div(10_000u128)
-            .ok_or(MarketError::MathError)?;
-
-        let cranker_reward = (fee as u128)
-            .checked_mul(market.cranker_reward_bps as u128)
-            .ok_or(MarketError::MathError)?
-            .checked_div(10_000u128)
-            .ok_or(MarketError::MathError)?;
-
-        let remaining_fee = fee
-            .checked_sub(cranker_reward)
-            .ok_or(MarketError::MathError)?;
-
-        let taker_is_buyer = event.taker_side == OrderSide::Buy as u8;
-        if event.event_type == EventType::Fill as u8 {
-            if event.makers_open_orders == market.key() {
-                let outcome = if event.outcome == OutcomeSide::Yes as u8 {
-                    OutcomeSide::Yes as u8
-                } else {
-                    OutcomeSide::No as u8
-                };
 
-                let mut q_yes = market.q_yes;
-                let mut q_no = market.q_no;
-                let b_fp = market.b_liquidity;
-
-                let cost_fp = amm_execute_buy(
-                    &mut q_yes,
-                    &mut q_no,
-                    b_fp as u128,
-                    outcome,
+        if event.event_type == EventType::Fill as u8 {
+            let mut taker_oo: Account<OpenOrder> = Account::try_from(taker_oo_info)?;
+            require!(
+                taker_oo.key() == event.taker_open_orders,
+                MarketError::NoMatchingOrder
+            );
+            let taker_outcome: Account<TokenAccount> = Account::try_from(taker_outcome_info)?;
+
+            let usdc_amount = (event.price)
+                .checked_mul(event.quantity as u128)
+                .ok_or(MarketError::MathError)?
+                .checked_div(PRICE_PRECISION_SCALE)
+                .ok_or(MarketError::MathError)? as u64;
+
+            // the tier comes from the taker's volume before this fill; a buy is charged the tiered
+            // rate and unlock_buy returns what it reserved above that
+            let mut taker_stats = load_taker_stats(taker_stats_info, &taker_oo.owner)?;
+            let taker_fee_bps = match taker_stats.as_mut() {
+                Some(stats) => {
+                    stats.roll(now);
+                    ctx.accounts
+                        .global_config
+                        .taker_fee_bps(market.taker_fee_bps, stats.rolling_notional())
+                }
+                None => market.taker_fee_bps,
+            };
+
+            let taker_is_buyer = event.taker_side == OrderSide::Buy as u8;
+            let is_amm = event.makers_open_orders == market.key();
+            // the bought tokens land in taker_outcome on both the book and the AMM path, and the
+            // cranker picks the account
+            if taker_is_buyer {
+                require!(
+                    taker_outcome.owner == taker_oo.owner
+                        && taker_outcome.mint == market.outcome_mints[event.outcome as usize],
+                    MarketError::Unauthorized
+                );
+            }
+            let (taker_fee, maker_fee);
+            if is_amm {
+                // matched before any pause, so it settles like every other queued event; the AMM
+                // only sells, match_request never routes a sell to it
+                require!(taker_is_buyer, MarketError::InvalidSide);
+                let n_outcomes = market.num_outcomes as usize;
+                let mut q_outcomes = market.q_outcomes;
+                let cost = amm_execute_buy(
+                    &mut q_outcomes[..n_outcomes],
+                    market.b_liquidity as u128,
+                    event.outcome,
                     event.quantity as u128,
                 )?;
-                let cost_u64 = cost_fp
-                    .checked_div(FP_SCALE)
-                    .ok_or(MarketError::MathError)? as u64;
-
-                let pay_amount = cost_u64
-                    .checked_sub(fee as u64)
+                let cost_u64 = u64::try_from(cost).map_err(|_| MarketError::MathError)?;
+
+                // the AMM is never charged or rebated, the taker pays its fee on top of the cost
+                taker_fee =
+                    Market::fee_on(market.fee_base(cost_u64, event.quantity), taker_fee_bps)?;
+                maker_fee = 0;
+                let charged = cost_u64
+                    .checked_add(taker_fee)
                     .ok_or(MarketError::MathError)?;
 
-                let cpi_ctx = CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.vault_usdc.to_account_info(),
-                        to: maker_usdc.to_account_info(),
-                        authority: market.to_account_info(),
-                    },
-                    seeds,
-                );
-
-                transfer(cpi_ctx, pay_amount)?;
-
-                let cranker_usdc_account: Account<TokenAccount> =
-                    Account::try_from(&cranker_usdc_account)?;
-                if cranker_reward > 0 {
-                    let cpi_ctx = CpiContext::new_with_signer(
-                        ctx.accounts.token_program.to_account_info(),
-                        Transfer {
-                            from: ctx.accounts.vault_usdc.to_account_info(),
-                            to: cranker_usdc_account.to_account_info(),
-                            authority: market.to_account_info(),
-                        },
-                        seeds,
-                    );
-
-                    transfer(cpi_ctx, cranker_reward as u64)?;
+                // earlier AMM fills can move the price past what the buy reserved at its limit;
+                // the order is then refunded rather than left to stall the queue
+                if charged > slot_buy_lock(market, &taker_oo, event.taker_slot, event.quantity)? {
+                    unlock_buy(market, &mut taker_oo, event.taker_slot, event.quantity, 0)?;
+                    fill_order_slot(&mut taker_oo, event.taker_slot, event.quantity)?;
+                    taker_oo.exit(&crate::ID)?;
+
+                    emit!(OrderCancelled {
+                        seq: market.next_event_seq()?,
+                        market: market.key(),
+                        open_order: event.taker_open_orders,
+                        order_id: event.order_id,
+                        side: event.taker_side,
+                        outcome: event.outcome,
+                        price: event.price,
+                        quantity: event.quantity,
+                        owner_slot: event.taker_slot,
+                    });
+                    processed_events += 1;
+                    continue;
                 }
 
-                if remaining_fee > 0 {
-                    let cpi_ctx = CpiContext::new_with_signer(
-                        ctx.accounts.token_program.to_account_info(),
-                        Transfer {
-                            from: ctx.accounts.vault_usdc.to_account_info(),
-                            to: ctx.accounts.fee_vault_usdc.to_account_info(),
-                            authority: market.to_account_info(),
-                        },
-                        seeds,
-                    );
-
-                    transfer(cpi_ctx, remaining_fee as u64)?;
-                }
-
-                let mint_acc = if outcome == OutcomeSide::Yes as u8 {
-                    &ctx.accounts.yes_mint
-                } else {
-                    &ctx.accounts.no_mint
-                };
-
+                // the cost stays in vault_usdc as collateral for the minted tokens, which is what
+                // net_deposits records below
                 let cpi_ctx = CpiContext::new_with_signer(
                     ctx.accounts.token_program.to_account_info(),
                     MintTo {
-                        mint: mint_acc.to_account_info(),
+                        mint: outcome_mint_info.clone(),
                         to: taker_outcome.to_account_info(),
                         authority: market.to_account_info(),
                     },
@@ -211,48 +225,77 @@ pub fn consume_events_handler<'info>(
 
                 mint_to(cpi_ctx, event.quantity)?;
 
-                market.q_no = q_no;
-                market.q_yes = q_yes;
+                market.q_outcomes = q_outcomes;
+                market.record_mint(event.outcome, event.quantity)?;
+                market.net_deposits[event.outcome as usize] = market.net_deposits
+                    [event.outcome as usize]
+                    .checked_add(cost_u64)
+                    .ok_or(MarketError::MathError)?;
 
-                if taker_is_buyer {
-                    taker_oo.locked_quote = taker_oo
-                        .locked_quote
-                        .checked_sub(cost_u64 as u128)
+                unlock_buy(
+                    market,
+                    &mut taker_oo,
+                    event.taker_slot,
+                    event.quantity,
+                    charged,
+                )?;
+                fill_order_slot(&mut taker_oo, event.taker_slot, event.quantity)?;
+            } else {
+                let mut maker_oo: Account<OpenOrder> = Account::try_from(maker_oo_info)?;
+                require!(
+                    maker_oo.key() == event.makers_open_orders,
+                    MarketError::NoMatchingOrder
+                );
+                (taker_fee, maker_fee) =
+                    market.fill_fees(usdc_amount, event.quantity, taker_fee_bps)?;
+
+                // USDC goes to the seller and outcome tokens to the buyer, whichever side took
+                let (seller_usdc, seller_proceeds, buyer_outcome) = if taker_is_buyer {
+                    require!(
+                        maker_usdc.owner == maker_oo.owner,
+                        MarketError::Unauthorized
+                    );
+                    let maker_proceeds = usdc_amount
+                        .checked_add_signed(-maker_fee)
                         .ok_or(MarketError::MathError)?;
+                    (
+                        maker_usdc.to_account_info(),
+                        maker_proceeds,
+                        taker_outcome.to_account_info(),
+                    )
                 } else {
-                    taker_oo.locked_base = taker_oo
-                        .locked_base
-                        .checked_sub(event.quantity as u128)
+                    let taker_usdc: Account<TokenAccount> = Account::try_from(taker_usdc_info)?;
+                    require!(
+                        taker_usdc.owner == taker_oo.owner && maker_outcome.owner == maker_oo.owner,
+                        MarketError::Unauthorized
+                    );
+                    let taker_proceeds = usdc_amount
+                        .checked_sub(taker_fee)
                         .ok_or(MarketError::MathError)?;
-                }
-            } else {
-                let payout_amount = usdc_amount
-                    .checked_sub(fee as u64)
-                    .ok_or(MarketError::MathError)?;
+                    (
+                        taker_usdc.to_account_info(),
+                        taker_proceeds,
+                        maker_outcome.to_account_info(),
+                    )
+                };
 
                 let cpi_ctx = CpiContext::new_with_signer(
                     ctx.accounts.token_program.to_account_info(),
                     Transfer {
                         from: ctx.accounts.vault_usdc.to_account_info(),
-                        to: maker_usdc.to_account_info(),
+                        to: seller_usdc,
                         authority: market.to_account_info(),
                     },
                     seeds,
                 );
 
-                transfer(cpi_ctx, payout_amount)?;
-
-                let from_vault = if event.outcome == OutcomeSide::Yes as u8 {
-                    ctx.accounts.vault_yes.to_account_info()
-                } else {
-                    ctx.accounts.vault_no.to_account_info()
-                };
+                transfer(cpi_ctx, seller_proceeds)?;
 
                 let cpi_ctx = CpiContext::new_with_signer(
                     ctx.accounts.token_program.to_account_info(),
                     Transfer {
-                        from: from_vault,
-                        to: taker_outcome.to_account_info(),
+                        from: outcome_vault_info.clone(),
+                        to: buyer_outcome,
                         authority: market.to_account_info(),
                     },
                     seeds,
@@ -260,137 +303,342 @@ pub fn consume_events_handler<'info>(
 
                 transfer(cpi_ctx, event.quantity)?;
 
-                let cranker_usdc_account: Account<TokenAccount> =
-                    Account::try_from(&cranker_usdc_account)?;
-                if cranker_reward > 0 {
-                    let cpi_ctx = CpiContext::new_with_signer(
-                        ctx.accounts.token_program.to_account_info(),
-                        Transfer {
-                            from: ctx.accounts.vault_usdc.to_account_info(),
-                            to: cranker_usdc_account.to_account_info(),
-                            authority: market.to_account_info(),
-                        },
-                        seeds,
-                    );
-
-                    transfer(cpi_ctx, cranker_reward as u64)?;
-                }
-
-                if remaining_fee > 0 {
-                    let cpi_ctx = CpiContext::new_with_signer(
-                        ctx.accounts.token_program.to_account_info(),
-                        Transfer {
-                            from: ctx.accounts.vault_usdc.to_account_info(),
-                            to: ctx.accounts.fee_vault_usdc.to_account_info(),
-                            authority: market.to_account_info(),
-                        },
-                        seeds,
-                    );
-
-                    transfer(cpi_ctx, remaining_fee as u64)?;
-                }
-
                 if taker_is_buyer {
-                    taker_oo.locked_quote = taker_oo
-                        .locked_quote
-                        .checked_sub(usdc_amount as u128)
+                    let charged = usdc_amount
+                        .checked_add(taker_fee)
                         .ok_or(MarketError::MathError)?;
+                    unlock_buy(
+                        market,
+                        &mut taker_oo,
+                        event.taker_slot,
+                        event.quantity,
+                        charged,
+                    )?;
                     maker_oo.locked_base = maker_oo
                         .locked_base
                         .checked_sub(event.quantity as u128)
                         .ok_or(MarketError::MathError)?;
                 } else {
+                    // the resting buy reserved a taker fee it didn't need, it pays its own maker
+                    // fee (or collects its rebate) instead
+                    let charged = usdc_amount
+                        .checked_add_signed(maker_fee)
+                        .ok_or(MarketError::MathError)?;
+                    unlock_buy(
+                        market,
+                        &mut maker_oo,
+                        event.maker_slot,
+                        event.quantity,
+                        charged,
+                    )?;
                     taker_oo.locked_base = taker_oo
                         .locked_base
                         .checked_sub(event.quantity as u128)
                         .ok_or(MarketError::MathError)?;
-                    maker_oo.locked_quote = maker_oo
-                        .locked_quote
-                        .checked_sub(usdc_amount as u128)
-                        .ok_or(MarketError::MathError)?;
                 }
+                fill_order_slot(&mut maker_oo, event.maker_slot, event.quantity)?;
+                fill_order_slot(&mut taker_oo, event.taker_slot, event.quantity)?;
+                maker_oo.exit(&crate::ID)?;
             }
-        } else if event.event_type == EventType::Cancel as u8 {
-            let mut found_slot_index: Option<usize> = None;
-            for (slot_index, slot) in maker_oo.slots.iter().enumerate() {
-                if slot.active && slot.order_id == event.order_id {
-                    found_slot_index = Some(slot_index);
-                    break;
-                }
+            taker_oo.exit(&crate::ID)?;
+            if let Some(stats) = taker_stats.as_mut() {
+                stats.record_fill(now, usdc_amount)?;
+                stats.exit(&crate::ID)?;
             }
-            if let Some(slot_index) = found_slot_index {
-                let slot = maker_oo.slots[slot_index];
 
-                if slot.side == OrderSide::Buy as u8 {
-                    let refund_fp = (slot.price as u128)
-                        .checked_mul(slot.quantity_remaining as u128)
-                        .ok_or(MarketError::MathError)?;
-                    let refund_amount = refund_fp
-                        .checked_div(PRICE_PRECISION_SCALE)
-                        .ok_or(MarketError::MathError)?
-                        as u64;
-
-                    let cpi_ctx = CpiContext::new_with_signer(
-                        ctx.accounts.token_program.to_account_info(),
-                        Transfer {
-                            from: ctx.accounts.vault_usdc.to_account_info(),
-                            to: maker_usdc.to_account_info(),
-                            authority: market.to_account_info(),
-                        },
-                        seeds,
-                    );
+            // the fee vault keeps the taker fee net of any rebate, so fills never pay out more
+            // than they collected
+            let fee = taker_fee
+                .checked_add_signed(maker_fee)
+                .ok_or(MarketError::InvalidFeeSchedule)?;
+            if fee > 0 {
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.vault_usdc.to_account_info(),
+                        to: ctx.accounts.fee_vault_usdc.to_account_info(),
+                        authority: market.to_account_info(),
+                    },
+                    seeds,
+                );
 
-                    transfer(cpi_ctx, refund_amount)?;
+                transfer(cpi_ctx, fee)?;
+            }
 
-                    maker_oo.locked_quote = maker_oo
-                        .locked_quote
-                        .checked_sub(refund_amount as u128)
-                        .ok_or(MarketError::MathError)?;
-                    maker_oo.slots[slot_index].active = false;
-                    maker_oo.slots[slot_index].quantity_remaining = 0;
-                } else {
-                    let refund_quantity = slot.quantity_remaining;
-                    let from_vault = if slot.outcome == OutcomeSide::Yes as u8 {
-                        ctx.accounts.yes_mint.to_account_info()
-                    } else {
-                        ctx.accounts.no_mint.to_account_info()
-                    };
-
-                    let cpi_ctx = CpiContext::new_with_signer(
-                        ctx.accounts.token_program.to_account_info(),
-                        Transfer {
-                            from: from_vault,
-                            to: maker_outcome.to_account_info(),
-                            authority: market.to_account_info(),
-                        },
-                        seeds,
-                    );
+            // the referrer's cut of the taker fee comes out of what the fee vault kept
+            let mut referral_fee = 0u64;
+            if let Some(mut ledger) =
+                load_referrer_ledger(referrer_ledger_info, &market.key(), &taker_oo.referrer)?
+            {
+                referral_fee =
+                    Market::fee_on(taker_fee, ctx.accounts.global_config.referral_share_bps)?
+                        .min(fee);
+                ledger.accrued = ledger
+                    .accrued
+                    .checked_add(referral_fee)
+                    .ok_or(MarketError::MathError)?;
+                ledger.referred_fills = ledger
+                    .referred_fills
+                    .checked_add(1)
+                    .ok_or(MarketError::MathError)?;
+                ledger.referred_volume = ledger
+                    .referred_volume
+                    .checked_add(usdc_amount)
+                    .ok_or(MarketError::MathError)?;
+                ledger.referred_fees = ledger
+                    .referred_fees
+                    .checked_add(taker_fee)
+                    .ok_or(MarketError::MathError)?;
+                ledger.exit(&crate::ID)?;
+                market.referral_rewards_owed = market
+                    .referral_rewards_owed
+                    .checked_add(referral_fee)
+                    .ok_or(MarketError::MathError)?;
+            }
 
-                    transfer(cpi_ctx, refund_quantity)?;
+            let cranker_reward = (fee
+                .checked_sub(referral_fee)
+                .ok_or(MarketError::MathError)? as u128)
+                .checked_mul(market.cranker_reward_bps as u128)
+                .ok_or(MarketError::MathError)?
+                .checked_div(10_000u128)
+                .ok_or(MarketError::MathError)?;
+
+            // the cranker's share stays in the fee vault and is claimed later from the ledger
+            accrued_reward = accrued_reward
+                .checked_add(cranker_reward as u64)
+                .ok_or(MarketError::MathError)?;
+
+            emit!(OrderFilled {
+                seq: market.next_event_seq()?,
+                market: market.key(),
+                maker_open_orders: event.makers_open_orders,
+                taker_open_orders: event.taker_open_orders,
+                order_id: event.order_id,
+                taker_side: event.taker_side,
+                outcome: event.outcome,
+                price: event.price,
+                quantity: event.quantity,
+                taker_fee,
+                maker_fee,
+                referral_fee,
+                is_amm,
+            });
+        } else if event.event_type == EventType::Cancel as u8 {
+            let mut maker_oo: Account<OpenOrder> = Account::try_from(maker_oo_info)?;
+            require!(
+                maker_oo.key() == event.makers_open_orders,
+                MarketError::NoMatchingOrder
+            );
+            require!(
+                maker_usdc.owner == maker_oo.owner && maker_outcome.owner == maker_oo.owner,
+                MarketError::Unauthorized
+            );
+
+            let slot_index = event.maker_slot as usize;
+            require!(
+                slot_index < maker_oo.slots.len(),
+                MarketError::OrderNotFound
+            );
+            let slot = maker_oo.slots[slot_index];
+            require!(
+                slot.active && slot.order_id == event.order_id,
+                MarketError::OrderNotFound
+            );
+
+            if slot.side == OrderSide::Buy as u8 {
+                let refund_amount = buy_lock(market, slot.price, event.quantity)?;
 
-                    maker_oo.locked_base = maker_oo
-                        .locked_base
-                        .checked_sub(refund_quantity as u128)
-                        .ok_or(MarketError::MathError)?;
-                    maker_oo.slots[slot_index].active = false;
-                    maker_oo.slots[slot_index].quantity_remaining = 0;
-                }
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.vault_usdc.to_account_info(),
+                        to: maker_usdc.to_account_info(),
+                        authority: market.to_account_info(),
+                    },
+                    seeds,
+                );
+
+                transfer(cpi_ctx, refund_amount)?;
+
+                maker_oo.locked_quote = maker_oo
+                    .locked_quote
+                    .checked_sub(refund_amount as u128)
+                    .ok_or(MarketError::MathError)?;
+                market.release_quote(refund_amount)?;
             } else {
-                return err!(MarketError::OrderNotFound);
+                let refund_quantity = event.quantity;
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: outcome_vault_info.clone(),
+                        to: maker_outcome.to_account_info(),
+                        authority: market.to_account_info(),
+                    },
+                    seeds,
+                );
+
+                transfer(cpi_ctx, refund_quantity)?;
+
+                maker_oo.locked_base = maker_oo
+                    .locked_base
+                    .checked_sub(refund_quantity as u128)
+                    .ok_or(MarketError::MathError)?;
             }
+
+            release_order_slot(&mut maker_oo, event.maker_slot)?;
+            maker_oo.exit(&crate::ID)?;
+
+            emit!(OrderCancelled {
+                seq: market.next_event_seq()?,
+                market: market.key(),
+                open_order: event.makers_open_orders,
+                order_id: event.order_id,
+                side: slot.side,
+                outcome: slot.outcome,
+                price: event.price,
+                quantity: event.quantity,
+                owner_slot: event.maker_slot,
+            });
         } else {
             return err!(MarketError::InvalidArgument);
         }
+
+        processed_events += 1;
     }
 
     event_queue.head = event_queue
         .head
-        .checked_add(n_events as u64)
+        .checked_add(processed_events as u64)
         .ok_or(MarketError::MathError)?;
     event_queue.count = event_queue
         .count
-        .checked_sub(n_events as u64)
+        .checked_sub(processed_events as u64)
+        .ok_or(MarketError::MathError)?;
+
+    let cranker_ledger = &mut ctx.accounts.cranker_ledger;
+    cranker_ledger.accrued = cranker_ledger
+        .accrued
+        .checked_add(accrued_reward)
         .ok_or(MarketError::MathError)?;
+    cranker_ledger.events_consumed = cranker_ledger
+        .events_consumed
+        .checked_add(processed_events as u64)
+        .ok_or(MarketError::MathError)?;
+    market.crank_rewards_owed = market
+        .crank_rewards_owed
+        .checked_add(accrued_reward)
+        .ok_or(MarketError::MathError)?;
+
+    debug_check_solvency(market, &mut ctx.accounts.vault_usdc)?;
+
+    let progress = CrankProgress {
+        market: market.key(),
+        crank_type: CrankType::ConsumeEvents,
+        processed: processed_events as u16,
+        remaining: event_queue.count,
+        compute_exhausted,
+    };
+    emit!(progress);
+    Ok(progress)
+}
 
+// a buy locked notional plus the fee reserve at its limit price for its whole quantity; a fill
+// releases its units' share of that lock and whatever it didn't charge goes to free_quote
+pub(crate) fn unlock_buy(
+    market: &mut Market,
+    open_order: &mut OpenOrder,
+    slot_index: u16,
+    quantity: u64,
+    charged: u64,
+) -> Result<()> {
+    let reserved = slot_buy_lock(market, open_order, slot_index, quantity)?;
+    let refund = reserved
+        .checked_sub(charged)
+        .ok_or(MarketError::InsufficientBalance)?;
+
+    open_order.locked_quote = open_order
+        .locked_quote
+        .checked_sub(reserved as u128)
+        .ok_or(MarketError::MathError)?;
+    open_order.free_quote = open_order
+        .free_quote
+        .checked_add(refund as u128)
+        .ok_or(MarketError::MathError)?;
+    market.release_quote(reserved)?;
+    market.lock_quote(refund)?;
     Ok(())
 }
+
+// the part of a buy slot's lock that `quantity` of its open units account for, measured against
+// what is still open so the fills and a final cancel add up to exactly the original lock
+fn slot_buy_lock(
+    market: &Market,
+    open_order: &OpenOrder,
+    slot_index: u16,
+    quantity: u64,
+) -> Result<u64> {
+    let slot = open_order
+        .slots
+        .get(slot_index as usize)
+        .filter(|slot| slot.active)
+        .ok_or(MarketError::OrderNotFound)?;
+    let still_open = slot
+        .quantity_remaining
+        .checked_sub(quantity)
+        .ok_or(MarketError::MathError)?;
+    let reserved = buy_lock(market, slot.price, slot.quantity_remaining)?
+        .checked_sub(buy_lock(market, slot.price, still_open)?)
+        .ok_or(MarketError::MathError)?;
+    Ok(reserved)
+}
+
+// what place_request locks for a buy of `quantity` at `price`
+pub(crate) fn buy_lock(market: &Market, price: u64, quantity: u64) -> Result<u64> {
+    let notional = (price as u128)
+        .checked_mul(quantity as u128)
+        .ok_or(MarketError::MathError)?
+        .checked_div(PRICE_PRECISION_SCALE)
+        .ok_or(MarketError::MathError)? as u64;
+    let lock = notional
+        .checked_add(market.taker_fee_reserve(notional, quantity)?)
+        .ok_or(MarketError::MathError)?;
+    Ok(lock)
+}
+
+// the taker's TraderStats, or None for a trader who never created one; anything else in the slot
+// is rejected so a cranker can't withhold a taker's tier
+fn load_taker_stats<'info>(
+    info: &'info AccountInfo<'info>,
+    trader: &Pubkey,
+) -> Result<Option<Account<'info, TraderStats>>> {
+    if info.owner == &crate::ID {
+        let stats: Account<TraderStats> = Account::try_from(info)?;
+        require!(stats.trader == *trader, MarketError::Unauthorized);
+        return Ok(Some(stats));
+    }
+    let (expected, _) =
+        Pubkey::find_program_address(&[TRADER_STATS_SEED, trader.as_ref()], &crate::ID);
+    require!(info.key() == expected, MarketError::Unauthorized);
+    Ok(None)
+}
+
+// the ledger of the taker's referrer on this market, or None when the taker has no referrer;
+// place_request only records a referrer together with its ledger, so a missing one fails the fill
+// instead of handing the referral share to the protocol
+fn load_referrer_ledger<'info>(
+    info: &'info AccountInfo<'info>,
+    market: &Pubkey,
+    referrer: &Pubkey,
+) -> Result<Option<Account<'info, ReferrerLedger>>> {
+    if *referrer == Pubkey::default() {
+        return Ok(None);
+    }
+    require!(info.owner == &crate::ID, MarketError::ReferrerLedgerMissing);
+    let ledger: Account<ReferrerLedger> = Account::try_from(info)?;
+    require!(
+        ledger.referrer == *referrer && ledger.market == *market,
+        MarketError::Unauthorized
+    );
+    Ok(Some(ledger))
+}
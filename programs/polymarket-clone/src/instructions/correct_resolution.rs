@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::{
+    constants::VAULT_USDC_SEED,
+    error::MarketError,
+    events::MarketResolved,
+    state::{Market, MarketStatus, ResolutionMode},
+    utils::debug_check_solvency,
+};
+
+#[derive(Accounts)]
+pub struct CorrectResolution<'info> {
+    pub oracle: Signer<'info>,
+
+    #[account(mut , has_one = oracle @ MarketError::Unauthorized)]
+    pub market: Account<'info, Market>,
+
+    #[account(seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
+    pub vault_usdc: Account<'info, TokenAccount>,
+}
+
+// on Authority markets the oracle can replace its own wrong resolution while the challenge period
+// is running; the period is not restarted, so claims open when they would have anyway. Outcomes
+// from a committee, a feed, a proposal or a cancellation are only ever changed by that resolver
+pub fn correct_resolution_handler(
+    ctx: Context<CorrectResolution>,
+    payout_numerators: Vec<u64>,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let market = &mut ctx.accounts.market;
+    require!(
+        market.resolution_mode == ResolutionMode::Authority,
+        MarketError::InvalidResolutionMode
+    );
+    require!(
+        market.is_resolved() && market.status != MarketStatus::Cancelled,
+        MarketError::InvalidMarketStatus
+    );
+    require!(
+        now < market.claims_open_at()?,
+        MarketError::ChallengePeriodElapsed
+    );
+
+    let resolved_at = market.resolved_at;
+    market.apply_payout_vector(&payout_numerators, now)?;
+    market.resolved_at = resolved_at;
+
+    emit!(MarketResolved {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        resolver: ctx.accounts.oracle.key(),
+        status: market.status,
+        payout_numerators: market.payout_numerators.to_vec(),
+        payout_denominator: market.payout_denominator,
+        claimable_at: market.claims_open_at()?,
+    });
+
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
+
+    Ok(())
+}
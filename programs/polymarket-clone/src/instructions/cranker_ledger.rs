@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::CRANKER_LEDGER_SEED,
+    state::{CrankerLedger, Market},
+};
+
+#[derive(Accounts)]
+pub struct CreateCrankerLedger<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = cranker,
+        space = 8 + std::mem::size_of::<CrankerLedger>(),
+        seeds = [CRANKER_LEDGER_SEED , market.key().as_ref() , cranker.key().as_ref()],
+        bump
+    )]
+    pub cranker_ledger: Account<'info, CrankerLedger>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn cranker_ledger_handler(ctx: Context<CreateCrankerLedger>) -> Result<()> {
+    let cranker_ledger = &mut ctx.accounts.cranker_ledger;
+    cranker_ledger.cranker = ctx.accounts.cranker.key();
+    cranker_ledger.market = ctx.accounts.market.key();
+    cranker_ledger.accrued = 0;
+    cranker_ledger.total_claimed = 0;
+    cranker_ledger.requests_matched = 0;
+    cranker_ledger.events_consumed = 0;
+    cranker_ledger.bump = ctx.bumps.cranker_ledger;
+
+    Ok(())
+}
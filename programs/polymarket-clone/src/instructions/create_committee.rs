@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::COMMITTEE_SEED,
+    error::MarketError,
+    events::CommitteeRotated,
+    state::{Market, ResolutionMode, ResolverCommittee},
+};
+
+#[derive(Accounts)]
+pub struct CreateCommittee<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(mut , has_one = creator @ MarketError::Unauthorized)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + std::mem::size_of::<ResolverCommittee>(),
+        seeds = [COMMITTEE_SEED , market.key().as_ref()],
+        bump
+    )]
+    pub committee: Account<'info, ResolverCommittee>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_committee_handler(
+    ctx: Context<CreateCommittee>,
+    members: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    require!(
+        market.resolution_mode == ResolutionMode::Committee,
+        MarketError::InvalidResolutionMode
+    );
+    // members have to be known while the market still trades, not picked after the fact
+    require!(
+        Clock::get()?.unix_timestamp < market.end_ts,
+        MarketError::InvalidMarketStatus
+    );
+    ResolverCommittee::validate_members(&members, threshold)?;
+
+    let committee = &mut ctx.accounts.committee;
+    committee.market = market.key();
+    committee.set_members(&members, threshold);
+    committee.bump = ctx.bumps.committee;
+
+    emit!(CommitteeRotated {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        members,
+        threshold,
+    });
+
+    Ok(())
+}
@@ -5,6 +5,7 @@ use crate::{
     constants::{OPEN_ORDER_SEED, VAULT_USDC_SEED},
     error::MarketError,
     state::{Market, OpenOrder},
+    utils::debug_check_solvency,
 };
 
 #[derive(Accounts)]
@@ -44,6 +45,9 @@ pub fn deposit_handler(ctx: Context<DepositToOpenOrders>, amount: u64) -> Result
         .free_quote
         .checked_add(amount as u128)
         .ok_or(MarketError::MathError)?;
+    ctx.accounts.market.lock_quote(amount)?;
+
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
 
     Ok(())
 }
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
+
+use crate::{
+    constants::{BOND_VAULT_SEED, PROPOSAL_SEED},
+    error::MarketError,
+    events::ResolutionDisputed,
+    state::{Market, MarketStatus, ResolutionProposal},
+};
+
+#[derive(Accounts)]
+pub struct DisputeResolution<'info> {
+    #[account(mut)]
+    pub disputer: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED , market.key().as_ref()],
+        bump = proposal.bump,
+        has_one = market @ MarketError::InvalidArgument
+    )]
+    pub proposal: Account<'info, ResolutionProposal>,
+
+    #[account(mut , seeds = [BOND_VAULT_SEED , market.key().as_ref()] , bump)]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub disputer_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn dispute_resolution_handler(ctx: Context<DisputeResolution>, outcome: u8) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let proposal = &mut ctx.accounts.proposal;
+    require!(
+        ctx.accounts.market.status == MarketStatus::Proposed,
+        MarketError::InvalidMarketStatus
+    );
+    require!(!proposal.is_disputed(), MarketError::AlreadyDisputed);
+    let liveness_ends = proposal
+        .proposed_at
+        .checked_add(ctx.accounts.market.liveness_secs)
+        .ok_or(MarketError::MathError)?;
+    require!(now < liveness_ends, MarketError::LivenessElapsed);
+    require!(
+        outcome < ctx.accounts.market.num_outcomes && outcome != proposal.proposed_outcome,
+        MarketError::InvalidOutcome
+    );
+
+    // the disputer matches the proposer's bond and names the outcome it argues for, the arbiter's
+    // ruling decides who takes both
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.disputer_usdc.to_account_info(),
+            to: ctx.accounts.bond_vault.to_account_info(),
+            authority: ctx.accounts.disputer.to_account_info(),
+        },
+    );
+    transfer(cpi_ctx, proposal.bond)?;
+
+    proposal.disputer = ctx.accounts.disputer.key();
+    proposal.disputed_at = now;
+    proposal.disputed_outcome = outcome;
+
+    let market = &mut ctx.accounts.market;
+    emit!(ResolutionDisputed {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        disputer: proposal.disputer,
+        outcome,
+        bond: proposal.bond,
+    });
+
+    Ok(())
+}
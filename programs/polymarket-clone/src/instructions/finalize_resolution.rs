@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
+
+use crate::{
+    constants::{BOND_VAULT_SEED, MARKET_SEED, PROPOSAL_SEED, VAULT_USDC_SEED},
+    error::MarketError,
+    events::{BondSettled, MarketResolved},
+    state::{Market, MarketStatus, ResolutionProposal},
+    utils::debug_check_solvency,
+};
+
+// permissionless: anyone can finalize an undisputed proposal once its liveness window is over
+#[derive(Accounts)]
+pub struct FinalizeResolution<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
+    pub vault_usdc: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED , market.key().as_ref()],
+        bump = proposal.bump,
+        has_one = market @ MarketError::InvalidArgument
+    )]
+    pub proposal: Account<'info, ResolutionProposal>,
+
+    #[account(mut , seeds = [BOND_VAULT_SEED , market.key().as_ref()] , bump)]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(mut , constraint = proposer_usdc.owner == proposal.proposer @ MarketError::Unauthorized)]
+    pub proposer_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn finalize_resolution_handler(ctx: Context<FinalizeResolution>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let proposal = &mut ctx.accounts.proposal;
+    require!(
+        ctx.accounts.market.status == MarketStatus::Proposed,
+        MarketError::InvalidMarketStatus
+    );
+    require!(!proposal.is_disputed(), MarketError::AlreadyDisputed);
+    let liveness_ends = proposal
+        .proposed_at
+        .checked_add(ctx.accounts.market.liveness_secs)
+        .ok_or(MarketError::MathError)?;
+    require!(now >= liveness_ends, MarketError::LivenessNotElapsed);
+
+    let bump = ctx.accounts.market.bump;
+    let seeds: &[&[&[u8]]] = &[&[
+        MARKET_SEED,
+        &ctx.accounts.market.market_id.to_le_bytes(),
+        &[bump],
+    ]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.bond_vault.to_account_info(),
+            to: ctx.accounts.proposer_usdc.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        },
+        seeds,
+    );
+    transfer(cpi_ctx, proposal.bond)?;
+
+    proposal.settled = true;
+
+    let market = &mut ctx.accounts.market;
+    market.apply_resolution(proposal.proposed_outcome, now)?;
+
+    emit!(BondSettled {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        recipient: proposal.proposer,
+        amount: proposal.bond,
+    });
+    emit!(MarketResolved {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        resolver: proposal.proposer,
+        status: market.status,
+        payout_numerators: market.payout_numerators.to_vec(),
+        payout_denominator: market.payout_denominator,
+        claimable_at: market.claims_open_at()?,
+    });
+
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::GLOBAL_CONFIG_SEED,
+    error::MarketError,
+    program::PolymarketClone,
+    state::{FeeTier, GlobalConfig},
+};
+
+// the singleton config can only be claimed by whoever holds the program's upgrade authority
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key())
+            @ MarketError::Unauthorized
+    )]
+    pub program: Program<'info, PolymarketClone>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ MarketError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<GlobalConfig>(),
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        has_one = admin @ MarketError::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+#[repr(C)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+
+pub struct FeeSplitParams {
+    pub fee_authority: Pubkey,
+    pub treasury: Pubkey,
+    pub insurance_fund: Pubkey,
+    pub protocol_share_bps: u16,
+    pub creator_share_bps: u16,
+    pub insurance_share_bps: u16,
+    pub referral_share_bps: u16,
+}
+
+pub fn initialize_config_handler(
+    ctx: Context<InitializeConfig>,
+    fee_tiers: Vec<FeeTier>,
+) -> Result<()> {
+    GlobalConfig::validate_tiers(&fee_tiers)?;
+
+    let global_config = &mut ctx.accounts.global_config;
+    global_config.admin = ctx.accounts.admin.key();
+    global_config.set_tiers(&fee_tiers);
+    // everything goes to the admin until a split is configured
+    global_config.fee_authority = ctx.accounts.admin.key();
+    global_config.treasury = ctx.accounts.admin.key();
+    global_config.insurance_fund = ctx.accounts.admin.key();
+    global_config.protocol_share_bps = 10_000;
+    global_config.creator_share_bps = 0;
+    global_config.insurance_share_bps = 0;
+    global_config.referral_share_bps = 0;
+    global_config.bump = ctx.bumps.global_config;
+
+    Ok(())
+}
+
+pub fn set_fee_tiers_handler(ctx: Context<UpdateConfig>, fee_tiers: Vec<FeeTier>) -> Result<()> {
+    GlobalConfig::validate_tiers(&fee_tiers)?;
+    ctx.accounts.global_config.set_tiers(&fee_tiers);
+
+    Ok(())
+}
+
+pub fn set_fee_split_handler(ctx: Context<UpdateConfig>, params: FeeSplitParams) -> Result<()> {
+    GlobalConfig::validate_split(
+        params.protocol_share_bps,
+        params.creator_share_bps,
+        params.insurance_share_bps,
+    )?;
+    require!(
+        params.referral_share_bps <= 10_000,
+        MarketError::InvalidFeeSchedule
+    );
+
+    let global_config = &mut ctx.accounts.global_config;
+    global_config.fee_authority = params.fee_authority;
+    global_config.treasury = params.treasury;
+    global_config.insurance_fund = params.insurance_fund;
+    global_config.protocol_share_bps = params.protocol_share_bps;
+    global_config.creator_share_bps = params.creator_share_bps;
+    global_config.insurance_share_bps = params.insurance_share_bps;
+    global_config.referral_share_bps = params.referral_share_bps;
+
+    Ok(())
+}
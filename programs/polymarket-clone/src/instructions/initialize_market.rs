@@ -1,19 +1,23 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
 
 use crate::{
     constants::{
-        ASKS_SEEDS, BIDS_SEED, EVENT_QUEUE_SEED, FEE_VAULT_USDC, MARKET_SEED, REQUEST_QUEUE_SEED,
-        VAULT_NO_SEED, VAULT_USDC_SEED, VAULT_YES_SEED,
+        ASKS_SEEDS, BIDS_SEED, EVENT_QUEUE_SEED, FEE_VAULT_USDC, MARKET_SEED, MAX_OUTCOMES,
+        REQUEST_QUEUE_SEED, VAULT_NO_SEED, VAULT_USDC_SEED, VAULT_YES_SEED,
     },
     error::MarketError,
-    state::{EventQueue, Market, RequestQueue, Slab},
+    events::MarketCreated,
+    instructions::liquidity_for_subsidy,
+    state::{
+        EventQueue, FeeCurve, Market, MarketKind, PriceFeed, RefundMode, RequestQueue,
+        ResolutionMode, Slab,
+    },
     utils::initialize_slab,
 };
 
 #[derive(Accounts)]
 #[instruction(params:InitializeMarketParams)]
-
 pub struct InitializeMarketAccounts<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
@@ -122,6 +126,10 @@ pub struct InitializeMarketAccounts<'info> {
     )]
     pub fee_vault_usdc: Account<'info, TokenAccount>,
 
+    // funds the AMM subsidy, only needed when amm_subsidy is non-zero
+    #[account(mut , token::mint = usdc_mint , token::authority = admin)]
+    pub admin_usdc: Option<Account<'info, TokenAccount>>,
+
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
@@ -134,9 +142,30 @@ pub struct InitializeMarketParams {
     pub market_id: u64,
     pub question: String,
     pub description: String,
+    pub market_kind: u8,
+    pub num_outcomes: u8,
+    pub scalar_lower: i64,
+    pub scalar_upper: i64,
+    pub start_ts: i64,
     pub end_ts: i64,
-    pub fee_bps: u16,
+    pub maker_fee_bps: i16,
+    pub taker_fee_bps: u16,
+    pub fee_curve: u8,
     pub cranker_fee_bps: u16,
+    pub match_reward: u64,
+    pub resolution_mode: u8,
+    pub proposal_bond: u64,
+    pub liveness_secs: i64,
+    pub challenge_period_secs: i64,
+    pub resolution_deadline: i64,
+    pub refund_mode: u8,
+    pub price_feed: Pubkey,
+    pub price_feed_program: Pubkey,
+    pub strike_price: i64,
+    pub strike_expo: i32,
+    pub max_staleness_secs: i64,
+    pub max_confidence_bps: u16,
+    pub amm_subsidy: u64, // zero leaves the market without an AMM, market orders only take the book
 }
 
 pub fn initial_market_handler(
@@ -144,15 +173,67 @@ pub fn initial_market_handler(
     params: InitializeMarketParams,
 ) -> Result<()> {
     // Validate inputs
-    require!(params.fee_bps <= 10000, MarketError::InvalidArgument);
+    require!(params.taker_fee_bps <= 10000, MarketError::InvalidArgument);
+    // makers never pay more than takers, and a rebate can't exceed the taker fee that funds it
+    require!(
+        params.maker_fee_bps.unsigned_abs() <= params.taker_fee_bps,
+        MarketError::InvalidFeeSchedule
+    );
     require!(
         params.cranker_fee_bps <= 10000,
         MarketError::InvalidArgument
     );
+    let now = Clock::get()?.unix_timestamp;
+    require!(params.end_ts > now, MarketError::InvalidArgument);
     require!(
-        params.end_ts > Clock::get()?.unix_timestamp,
+        params.start_ts < params.end_ts,
         MarketError::InvalidArgument
     );
+    require!(params.liveness_secs >= 0, MarketError::InvalidArgument);
+    require!(
+        params.challenge_period_secs >= 0,
+        MarketError::InvalidArgument
+    );
+    require!(
+        params.resolution_deadline >= params.end_ts,
+        MarketError::InvalidArgument
+    );
+    let kind = MarketKind::from_u8(params.market_kind)?;
+    if kind == MarketKind::Categorical {
+        require!(
+            params.num_outcomes >= 2 && params.num_outcomes as usize <= MAX_OUTCOMES,
+            MarketError::InvalidArgument
+        );
+        require!(
+            params.resolution_mode != ResolutionMode::PriceFeed as u8,
+            MarketError::InvalidResolutionMode
+        );
+    } else {
+        require!(params.num_outcomes == 2, MarketError::InvalidArgument);
+    }
+    if kind == MarketKind::Scalar {
+        require!(
+            params.scalar_lower < params.scalar_upper,
+            MarketError::InvalidArgument
+        );
+        // proposals and the price feed adapter only speak YES / NO
+        require!(
+            params.resolution_mode == ResolutionMode::Authority as u8,
+            MarketError::InvalidResolutionMode
+        );
+    }
+    if params.resolution_mode == ResolutionMode::PriceFeed as u8 {
+        require!(
+            params.price_feed != Pubkey::default()
+                && params.price_feed_program != Pubkey::default(),
+            MarketError::InvalidArgument
+        );
+        require!(params.max_staleness_secs >= 0, MarketError::InvalidArgument);
+        require!(
+            params.max_confidence_bps <= 10000,
+            MarketError::InvalidArgument
+        );
+    }
     require!(
         params.question.len() <= crate::constants::MAX_MARKET_QUESTION,
         MarketError::InvalidArgument
@@ -166,27 +247,97 @@ pub fn initial_market_handler(
     market.creator = ctx.accounts.admin.key();
     market.question = params.question;
     market.description = params.description;
+    market.kind = kind;
+    market.scalar_lower = params.scalar_lower;
+    market.scalar_upper = params.scalar_upper;
+    market.start_ts = params.start_ts;
     market.end_ts = params.end_ts;
-    market.status = crate::state::MarketStatus::Open;
+    market.status = crate::state::MarketStatus::Pending;
+    // the first two outcomes are created here, categorical markets add the rest with add_outcome
+    market.num_outcomes = params.num_outcomes;
+    market.registered_outcomes = 2;
+    market.outcome_mints = [Pubkey::default(); MAX_OUTCOMES];
+    market.outcome_vaults = [Pubkey::default(); MAX_OUTCOMES];
+    market.outcome_mints[0] = ctx.accounts.yes_mint.key();
+    market.outcome_mints[1] = ctx.accounts.no_mint.key();
+    market.outcome_vaults[0] = ctx.accounts.vault_yes.key();
+    market.outcome_vaults[1] = ctx.accounts.vault_no.key();
+    market.refresh_status(now);
     market.asks = ctx.accounts.asks.key();
     market.bids = ctx.accounts.bids.key();
-    market.yes_mint = ctx.accounts.yes_mint.key();
-    market.no_mint = ctx.accounts.no_mint.key();
     market.usdc_mint = ctx.accounts.usdc_mint.key();
     market.vault_usdc = ctx.accounts.vault_usdc.key();
-    market.vault_yes = ctx.accounts.vault_yes.key();
-    market.vault_no = ctx.accounts.vault_no.key();
     market.event_queue = ctx.accounts.event_queue.key();
     market.request_queue = ctx.accounts.request_queue.key();
     market.fee_vault_usdc = ctx.accounts.fee_vault_usdc.key();
-    market.fee_bps = params.fee_bps;
+    market.maker_fee_bps = params.maker_fee_bps;
+    market.taker_fee_bps = params.taker_fee_bps;
+    market.fee_curve = FeeCurve::from_u8(params.fee_curve)?;
     market.cranker_reward_bps = params.cranker_fee_bps;
-    market.q_yes = 0;
-    market.q_no = 0;
-    market.b_liquidity = 1_000_00; // initial liquidity constant
+    market.match_reward = params.match_reward;
+    market.crank_rewards_owed = 0;
+    market.referral_rewards_owed = 0;
+    market.q_outcomes = [0; MAX_OUTCOMES];
+    market.amm_subsidy = params.amm_subsidy;
+    market.b_liquidity = if params.amm_subsidy > 0 {
+        liquidity_for_subsidy(params.amm_subsidy, params.num_outcomes)?
+    } else {
+        0
+    };
     market.oracle = ctx.accounts.admin.key();
+    market.resolution_mode = ResolutionMode::from_u8(params.resolution_mode)?;
+    market.proposal_bond = params.proposal_bond;
+    market.liveness_secs = params.liveness_secs;
+    market.price_feed = params.price_feed;
+    market.price_feed_program = params.price_feed_program;
+    market.strike_price = params.strike_price;
+    market.strike_expo = params.strike_expo;
+    market.max_staleness_secs = params.max_staleness_secs;
+    market.max_confidence_bps = params.max_confidence_bps;
+    market.feed_snapshot = PriceFeed::default();
+    market.resolved_value = 0;
+    market.payout_numerators = [0; MAX_OUTCOMES];
+    market.payout_denominator = 0;
+    market.resolved_at = 0;
+    market.challenge_period_secs = params.challenge_period_secs;
+    market.resolution_deadline = params.resolution_deadline;
+    market.refund_mode = RefundMode::from_u8(params.refund_mode)?;
+    market.net_deposits = [0; MAX_OUTCOMES];
+    market.quote_escrow = 0;
+    market.outcome_supply = [0; MAX_OUTCOMES];
+    market.event_seq = 0;
     market.bump = ctx.bumps.market;
 
+    emit!(MarketCreated {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        market_id: market.market_id,
+        creator: market.creator,
+        question: market.question.clone(),
+        num_outcomes: market.num_outcomes,
+        end_ts: market.end_ts,
+        maker_fee_bps: market.maker_fee_bps,
+        taker_fee_bps: market.taker_fee_bps,
+        fee_curve: market.fee_curve,
+    });
+
+    if params.amm_subsidy > 0 {
+        let admin_usdc = ctx
+            .accounts
+            .admin_usdc
+            .as_ref()
+            .ok_or(MarketError::InvalidArgument)?;
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: admin_usdc.to_account_info(),
+                to: ctx.accounts.vault_usdc.to_account_info(),
+                authority: ctx.accounts.admin.to_account_info(),
+            },
+        );
+        transfer(cpi_ctx, params.amm_subsidy)?;
+    }
+
     //initializing request queue
     ctx.accounts.request_queue.head = 0;
     ctx.accounts.request_queue.count = 0;
@@ -1,20 +1,46 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use solana_program::compute_units::sol_remaining_compute_units;
 
 use crate::{
-    constants::{ASKS_SEEDS, BIDS_SEED, EVENT_QUEUE_SEED, REQUEST_QUEUE_SEED},
+    constants::{
+        ASKS_SEEDS, BIDS_SEED, CRANKER_LEDGER_SEED, EVENT_QUEUE_SEED, FEE_VAULT_USDC,
+        MATCH_REQUEST_CU_RESERVE, PRICE_PRECISION_SCALE, REQUEST_QUEUE_SEED, VAULT_USDC_SEED,
+    },
     error::MarketError,
-    state::{Event, EventQueue, EventType, Market, OrderSide, RequestQueue, RequestType, Slab},
+    events::{CrankProgress, CrankType, OrderRested},
+    state::{
+        CrankerLedger, Event, EventQueue, EventType, Market, MarketStatus, OrderSide, RequestQueue,
+        RequestType, Slab,
+    },
     utils::{
-        allocate_order_entry, append_order_to_price, dequeue_requests, find_best_price_node_index,
-        pop_order_from_prices, push_event,
+        allocate_order_entry, append_order_to_price, book_key, debug_check_solvency,
+        dequeue_requests, event_queue_has_space, find_best_price_node_index, find_price_node_index,
+        key_price, peek_request, pop_order_from_prices, push_event, remove_order_from_price,
+        remove_price_node, update_head_request_quantity,
     },
 };
 
 #[derive(Accounts)]
 pub struct MatchRequest<'info> {
+    pub cranker: Signer<'info>,
+
     #[account(mut)]
     pub market: Account<'info, Market>,
 
+    #[account(seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
+    pub vault_usdc: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [CRANKER_LEDGER_SEED , market.key().as_ref() , cranker.key().as_ref()],
+        bump = cranker_ledger.bump
+    )]
+    pub cranker_ledger: Account<'info, CrankerLedger>,
+
+    #[account(seeds = [FEE_VAULT_USDC , &market.market_id.to_le_bytes()] , bump)]
+    pub fee_vault_usdc: Account<'info, TokenAccount>,
+
     #[account(mut , seeds = [BIDS_SEED , &market.market_id.to_le_bytes()] , bump)]
     pub bids: Account<'info, Slab>,
 
@@ -28,24 +54,99 @@ pub struct MatchRequest<'info> {
     pub event_queue: Account<'info, EventQueue>,
 }
 
-pub fn match_order_handler(ctx: Context<MatchRequest>, max_requests: u16) -> Result<()> {
-    let req = dequeue_requests(&mut ctx.accounts.request_queue, max_requests as usize)?;
-    for req in req.into_iter() {
+pub fn match_order_handler(ctx: Context<MatchRequest>, max_requests: u16) -> Result<CrankProgress> {
+    let mut processed_requests = 0usize;
+    let mut compute_exhausted = false;
+    let mut fill_rewards = 0u64;
+
+    let market = &mut ctx.accounts.market;
+    market.refresh_status(Clock::get()?.unix_timestamp);
+    if market.status == MarketStatus::Pending {
+        return err!(MarketError::MarketNotOpen);
+    }
+    // once trading stops, or while it is paused, the queue still drains: cancels go through and
+    // new orders are refunded, so a pause never holds anyone's collateral
+    let trading = market.status == MarketStatus::Open;
+
+    'requests: while processed_requests < max_requests as usize {
+        if sol_remaining_compute_units() < MATCH_REQUEST_CU_RESERVE {
+            compute_exhausted = true;
+            break;
+        }
+
+        let req = match peek_request(&ctx.accounts.request_queue) {
+            Some(req) => req,
+            None => break,
+        };
+
         if req.request_type == RequestType::CancelOrder as u8 {
+            if !event_queue_has_space(&ctx.accounts.event_queue) {
+                break;
+            }
+
+            let own_slab = if req.side == OrderSide::Buy as u8 {
+                &mut ctx.accounts.bids
+            } else {
+                &mut ctx.accounts.asks
+            };
+
+            // the order may already be fully filled, in which case there is nothing left to release
+            let removed_entry =
+                match find_price_node_index(own_slab, book_key(req.outcome, req.price)?) {
+                    Some(price_node_index) => remove_order_from_price(
+                        own_slab,
+                        price_node_index,
+                        req.order_id,
+                        req.open_order,
+                    )?,
+                    None => None,
+                };
+
+            if let Some(entry) = removed_entry {
+                let event = Event {
+                    event_type: EventType::Cancel as u8,
+                    makers_open_orders: req.open_order,
+                    maker_slot: req.owner_slot,
+                    taker_open_orders: Pubkey::default(),
+                    taker_side: req.side,
+                    taker_slot: 0,
+                    price: req.price,
+                    quantity: entry.quantity,
+                    order_id: req.order_id,
+                    outcome: req.outcome,
+                    timestamp: Clock::get()?.unix_timestamp,
+                };
+                push_event(&mut ctx.accounts.event_queue, event)?;
+            }
+
+            dequeue_requests(&mut ctx.accounts.request_queue, 1)?;
+            processed_requests += 1;
+            continue;
+        }
+
+        if !trading {
+            if !event_queue_has_space(&ctx.accounts.event_queue) {
+                break;
+            }
+
+            // releases everything the order locked, exactly like a cancel of a resting order
             let event = Event {
                 event_type: EventType::Cancel as u8,
-                makers_open_orders: req.owner,
-                maker_slot: 0,
+                makers_open_orders: req.open_order,
+                maker_slot: req.owner_slot,
                 taker_open_orders: Pubkey::default(),
                 taker_side: req.side,
                 taker_slot: 0,
-                price: 0,
-                quantity: 0,
+                price: req.price,
+                quantity: req.quantity,
                 order_id: req.order_id,
                 outcome: req.outcome,
                 timestamp: Clock::get()?.unix_timestamp,
             };
             push_event(&mut ctx.accounts.event_queue, event)?;
+
+            dequeue_requests(&mut ctx.accounts.request_queue, 1)?;
+            processed_requests += 1;
             continue;
         }
 
@@ -55,18 +156,19 @@ pub fn match_order_handler(ctx: Context<MatchRequest>, max_requests: u16) -> Res
             (&mut ctx.accounts.bids, &mut ctx.accounts.asks)
         };
 
+        // a request cut short by a full event queue resumes from the quantity stored at the head
         let mut left_quantity = req.quantity;
 
         loop {
             if left_quantity == 0 {
                 break;
             }
-            let maybe_best_price = find_best_price_node_index(&opposite_slab);
+            let maybe_best_price = find_best_price_node_index(opposite_slab, req.outcome);
             if maybe_best_price.is_none() {
                 break;
             }
             let best_price_index = maybe_best_price.ok_or(MarketError::MathError)?;
-            let best_price = opposite_slab.price_nodes[best_price_index as usize].key;
+            let best_price = key_price(opposite_slab.price_nodes[best_price_index as usize].key);
 
             let crossing = if req.side == OrderSide::Buy as u8 {
                 best_price <= req.price
@@ -82,6 +184,12 @@ pub fn match_order_handler(ctx: Context<MatchRequest>, max_requests: u16) -> Res
             if head_entry_index == -1 {
                 break;
             }
+
+            if !event_queue_has_space(&ctx.accounts.event_queue) {
+                update_head_request_quantity(&mut ctx.accounts.request_queue, left_quantity)?;
+                break 'requests;
+            }
+
             let entry = opposite_slab.order_entries[head_entry_index as usize];
             let matched_quantity = if entry.quantity > left_quantity {
                 left_quantity
@@ -94,7 +202,7 @@ pub fn match_order_handler(ctx: Context<MatchRequest>, max_requests: u16) -> Res
                 makers_open_orders: entry.open_order_owner,
                 maker_slot: entry.owner_slot,
                 taker_open_orders: req.open_order,
-                taker_slot: 0,
+                taker_slot: req.owner_slot,
                 taker_side: req.side,
                 price: best_price,
                 quantity: matched_quantity,
@@ -104,12 +212,18 @@ pub fn match_order_handler(ctx: Context<MatchRequest>, max_requests: u16) -> Res
             };
 
             push_event(&mut ctx.accounts.event_queue, event)?;
+            fill_rewards = fill_rewards
+                .checked_add(fill_reward(&ctx.accounts.market, &event)?)
+                .ok_or(MarketError::MathError)?;
 
             if entry.quantity > matched_quantity {
                 opposite_slab.order_entries[head_entry_index as usize].quantity =
                     entry.quantity - matched_quantity;
             } else {
                 pop_order_from_prices(opposite_slab, best_price_index)?;
+                if opposite_slab.price_nodes[best_price_index as usize].order_head == -1 {
+                    remove_price_node(opposite_slab, best_price_index)?;
+                }
             }
 
             left_quantity = left_quantity
@@ -117,24 +231,51 @@ pub fn match_order_handler(ctx: Context<MatchRequest>, max_requests: u16) -> Res
                 .ok_or(MarketError::MathError)?;
         }
 
-        if left_quantity > 0 {
-            let event = Event {
-                event_type: EventType::Fill as u8,
-                makers_open_orders: ctx.accounts.market.key(),
-                maker_slot: 0,
-                taker_open_orders: req.open_order,
-                taker_slot: 0,
-                taker_side: req.side,
-                price: req.price,
-                quantity: left_quantity,
-                order_id: req.order_id,
-                outcome: req.outcome,
-                timestamp: Clock::get()?.unix_timestamp,
-            };
+        // market buys take whatever the book could not fill from the AMM, which only sells; the
+        // rest of a market sell, or of a buy on a market without an AMM, is refunded and limit
+        // orders rest
+        if left_quantity > 0 && req.request_type == RequestType::MarketOrder as u8 {
+            if !event_queue_has_space(&ctx.accounts.event_queue) {
+                update_head_request_quantity(&mut ctx.accounts.request_queue, left_quantity)?;
+                break;
+            }
 
-            push_event(&mut ctx.accounts.event_queue, event)?;
-        }
-        if left_quantity > 0 {
+            if ctx.accounts.market.b_liquidity > 0 && req.side == OrderSide::Buy as u8 {
+                let event = Event {
+                    event_type: EventType::Fill as u8,
+                    makers_open_orders: ctx.accounts.market.key(),
+                    maker_slot: 0,
+                    taker_open_orders: req.open_order,
+                    taker_slot: req.owner_slot,
+                    taker_side: req.side,
+                    price: req.price,
+                    quantity: left_quantity,
+                    order_id: req.order_id,
+                    outcome: req.outcome,
+                    timestamp: Clock::get()?.unix_timestamp,
+                };
+
+                push_event(&mut ctx.accounts.event_queue, event)?;
+                fill_rewards = fill_rewards
+                    .checked_add(fill_reward(&ctx.accounts.market, &event)?)
+                    .ok_or(MarketError::MathError)?;
+            } else {
+                let event = Event {
+                    event_type: EventType::Cancel as u8,
+                    makers_open_orders: req.open_order,
+                    maker_slot: req.owner_slot,
+                    taker_open_orders: Pubkey::default(),
+                    taker_side: req.side,
+                    taker_slot: 0,
+                    price: req.price,
+                    quantity: left_quantity,
+                    order_id: req.order_id,
+                    outcome: req.outcome,
+                    timestamp: Clock::get()?.unix_timestamp,
+                };
+                push_event(&mut ctx.accounts.event_queue, event)?;
+            }
+        } else if left_quantity > 0 {
             // Insert unmatched order into own slab
             let own_slab = if req.side == OrderSide::Buy as u8 {
                 &mut ctx.accounts.bids
@@ -142,25 +283,91 @@ pub fn match_order_handler(ctx: Context<MatchRequest>, max_requests: u16) -> Res
                 &mut ctx.accounts.asks
             };
 
-            let price_node_index = crate::utils::insert_price_node_by_tree(own_slab, req.price)?;
+            let price_node_index = crate::utils::insert_price_node_by_tree(
+                own_slab,
+                book_key(req.outcome, req.price)?,
+            )?;
 
             let order_entry_index = allocate_order_entry(own_slab)?;
             let order_entry = &mut own_slab.order_entries[order_entry_index as usize];
             order_entry.order_id = req.order_id;
             order_entry.open_order_owner = req.open_order;
             order_entry.quantity = left_quantity;
-            order_entry.owner_slot = 0; // TODO: Find available slot in OpenOrder
+            order_entry.owner_slot = req.owner_slot;
             order_entry.reserved_amount = if req.side == OrderSide::Buy as u8 {
                 (req.price
                     .checked_mul(left_quantity as u128)
                     .ok_or(MarketError::MathError)?
-                    .checked_div(crate::constants::PRICE_PRECISION_SCALE)
+                    .checked_div(PRICE_PRECISION_SCALE)
                     .ok_or(MarketError::MathError)?) as u64
             } else {
                 0
             };
             append_order_to_price(own_slab, price_node_index, order_entry_index)?;
+
+            let market = &mut ctx.accounts.market;
+            emit!(OrderRested {
+                seq: market.next_event_seq()?,
+                market: market.key(),
+                open_order: req.open_order,
+                order_id: req.order_id,
+                side: req.side,
+                outcome: req.outcome,
+                price: req.price,
+                quantity: left_quantity,
+                owner_slot: req.owner_slot,
+            });
         }
+
+        dequeue_requests(&mut ctx.accounts.request_queue, 1)?;
+        processed_requests += 1;
     }
-    Ok(())
+
+    // matching is only rewarded for the fills it produced, never more than the fees not yet promised out
+    let market = &mut ctx.accounts.market;
+    let unallocated_fees = market.collectable_fees(ctx.accounts.fee_vault_usdc.amount);
+    let match_reward = fill_rewards.min(unallocated_fees);
+
+    let cranker_ledger = &mut ctx.accounts.cranker_ledger;
+    cranker_ledger.accrued = cranker_ledger
+        .accrued
+        .checked_add(match_reward)
+        .ok_or(MarketError::MathError)?;
+    cranker_ledger.requests_matched = cranker_ledger
+        .requests_matched
+        .checked_add(processed_requests as u64)
+        .ok_or(MarketError::MathError)?;
+    market.crank_rewards_owed = market
+        .crank_rewards_owed
+        .checked_add(match_reward)
+        .ok_or(MarketError::MathError)?;
+
+    debug_check_solvency(market, &mut ctx.accounts.vault_usdc)?;
+
+    let progress = CrankProgress {
+        market: market.key(),
+        crank_type: CrankType::MatchRequest,
+        processed: processed_requests as u16,
+        remaining: ctx.accounts.request_queue.count,
+        compute_exhausted,
+    };
+    emit!(progress);
+    Ok(progress)
+}
+
+// the flat match reward for one fill, capped by the fee that fill will pay at the market rates, so
+// churning cancels, non-crossing requests or dust self-trades earns nothing
+fn fill_reward(market: &Market, event: &Event) -> Result<u64> {
+    let notional = event
+        .price
+        .checked_mul(event.quantity as u128)
+        .ok_or(MarketError::MathError)?
+        .checked_div(PRICE_PRECISION_SCALE)
+        .ok_or(MarketError::MathError)? as u64;
+    let (taker_fee, maker_fee) =
+        market.fill_fees(notional, event.quantity, market.taker_fee_bps)?;
+    let fee = taker_fee
+        .checked_add_signed(maker_fee)
+        .ok_or(MarketError::MathError)?;
+    Ok(market.match_reward.min(fee))
 }
@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, Burn, Token, TokenAccount};
+
+use crate::{
+    constants::{MARKET_SEED, OPEN_ORDER_SEED, VAULT_USDC_SEED},
+    error::MarketError,
+    events::TokensMerged,
+    state::{Market, OpenOrder},
+    utils::debug_check_solvency,
+};
+
+// permissionless: converting complete sets into free_quote never makes the owner worse off
+#[derive(Accounts)]
+pub struct MergeOpenOrder<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
+    pub vault_usdc: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [OPEN_ORDER_SEED , market.key().as_ref() , open_order.owner.as_ref()],
+        bump = open_order.bump,
+        has_one = market @ MarketError::Unauthorized
+    )]
+    pub open_order: Account<'info, OpenOrder>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+// burns min(free_base) of every outcome from the outcome vaults and credits what those sets are worth to free_quote,
+// `outcome_accounts` holds one chunk of `stride` accounts per outcome starting with (outcome_mint, outcome_vault)
+pub fn merge_free_sets<'info>(
+    market: &mut Account<'info, Market>,
+    open_order: &mut Account<'info, OpenOrder>,
+    outcome_accounts: &[AccountInfo<'info>],
+    stride: usize,
+    token_program: &AccountInfo<'info>,
+) -> Result<u64> {
+    let n_outcomes = market.num_outcomes as usize;
+    require!(
+        outcome_accounts.len() == stride * n_outcomes,
+        MarketError::InvalidArgument
+    );
+    let sets = open_order.free_base[..n_outcomes]
+        .iter()
+        .copied()
+        .min()
+        .unwrap_or(0) as u64;
+    if sets == 0 {
+        return Ok(0);
+    }
+
+    let bump = market.bump;
+    let market_id = market.market_id.to_le_bytes();
+    let seeds: &[&[&[u8]]] = &[&[MARKET_SEED, &market_id, &[bump]]];
+
+    for (outcome, accounts) in outcome_accounts.chunks(stride).enumerate() {
+        let (outcome_mint, outcome_vault) = (&accounts[0], &accounts[1]);
+        require!(
+            market.outcome_mints[outcome] == outcome_mint.key(),
+            MarketError::InvalidOutcome
+        );
+        market.check_outcome_vault(outcome as u8, outcome_vault.key)?;
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.clone(),
+            Burn {
+                mint: outcome_mint.clone(),
+                from: outcome_vault.clone(),
+                authority: market.to_account_info(),
+            },
+            seeds,
+        );
+        burn(cpi_ctx, sets)?;
+
+        market.record_burn(outcome as u8, sets)?;
+        open_order.free_base[outcome] = open_order.free_base[outcome]
+            .checked_sub(sets as u128)
+            .ok_or(MarketError::MathError)?;
+    }
+
+    // the backing USDC never left vault_usdc, it is now owed to the OpenOrder instead, at the
+    // payout rates once the market has been cancelled
+    let value = market.complete_set_value(sets)?;
+    open_order.free_quote = open_order
+        .free_quote
+        .checked_add(value as u128)
+        .ok_or(MarketError::MathError)?;
+    market.lock_quote(value)?;
+    market.record_complete_set(sets, false)?;
+
+    emit!(TokensMerged {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        trader: open_order.owner,
+        amount: sets,
+    });
+
+    Ok(sets)
+}
+
+// remaining accounts: (outcome_mint, outcome_vault) for every outcome, in outcome order
+pub fn merge_open_order_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MergeOpenOrder<'info>>,
+) -> Result<()> {
+    merge_free_sets(
+        &mut ctx.accounts.market,
+        &mut ctx.accounts.open_order,
+        ctx.remaining_accounts,
+        2,
+        &ctx.accounts.token_program.to_account_info(),
+    )?;
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
+    Ok(())
+}
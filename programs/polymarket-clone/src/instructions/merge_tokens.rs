@@ -1,79 +1,91 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{
-    token::{burn, transfer, Burn, Transfer},
-    token_interface::{Mint, TokenAccount, TokenInterface},
-};
+use anchor_spl::token::{burn, transfer, Burn, Token, TokenAccount, Transfer};
 
 use crate::{
     constants::{MARKET_SEED, VAULT_USDC_SEED},
-    state::{Market, Vault},
+    error::MarketError,
+    events::TokensMerged,
+    state::{Market, MarketStatus},
+    utils::debug_check_solvency,
 };
 
 #[derive(Accounts)]
-#[instruction(params: MergeTokensParams)]
 pub struct MergeTokens<'info> {
-    #[account(mut)]
     pub trader: Signer<'info>,
 
-    #[account(mut)]
+    #[account(mut , has_one = vault_usdc @ MarketError::InvalidArgument)]
     pub market: Account<'info, Market>,
 
-    #[account(mut , seeds = [VAULT_USDC_SEED , &params.market_id.to_le_bytes()] , bump)]
-    pub vault_usdc: Account<'info, Vault>,
-
-    #[account(mut)]
-    pub trader_usdc: InterfaceAccount<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub trader_yes: InterfaceAccount<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub trader_no: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut , seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
+    pub vault_usdc: Account<'info, TokenAccount>,
 
-    pub yes_mint: InterfaceAccount<'info, Mint>,
-    pub no_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = market.usdc_mint,
+        token::authority = trader
+    )]
+    pub trader_usdc: Account<'info, TokenAccount>,
 
-    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[repr(C)]
 #[derive(AnchorDeserialize, AnchorSerialize, Debug, Clone)]
 
 pub struct MergeTokensParams {
-    amount: u64,
-    market_id: u64,
+    pub amount: u64,
 }
 
-pub fn merge_tokens_handler(ctx: Context<MergeTokens>, params: MergeTokensParams) -> Result<()> {
+// remaining accounts: (outcome_mint, trader_outcome_account) for every outcome, in outcome order
+pub fn merge_tokens_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MergeTokens<'info>>,
+    params: MergeTokensParams,
+) -> Result<()> {
     let market = &mut ctx.accounts.market;
+    market.refresh_status(Clock::get()?.unix_timestamp);
+    // merging only unwinds positions, so it stays available after trading stops and after a
+    // cancellation; a set then pays what claiming each of its outcomes would, which NetDeposits
+    // refund rates can leave below 1 USDC
+    match market.status {
+        MarketStatus::Pending => return err!(MarketError::MarketNotOpen),
+        MarketStatus::Paused => return err!(MarketError::MarketPaused),
+        _ => {}
+    }
+    require!(params.amount > 0, MarketError::InvalidArgument);
     let bump = market.bump;
-    let seed: &[&[&[u8]]] = &[&[MARKET_SEED, &market.market_id.to_le_bytes(), &[bump]]];
-
-    let burn_yes = Burn {
-        from: ctx.accounts.trader_yes.to_account_info(),
-        mint: ctx.accounts.yes_mint.to_account_info(),
-        authority: ctx.accounts.trader.to_account_info(),
-    };
-
-    burn(
-        CpiContext::new(ctx.accounts.token_program.to_account_info(), burn_yes),
-        params.amount,
-    )?;
-
-    let burn_no = Burn {
-        from: ctx.accounts.trader_no.to_account_info(),
-        mint: ctx.accounts.no_mint.to_account_info(),
-        authority: ctx.accounts.trader.to_account_info(),
-    };
-
-    burn(
-        CpiContext::new(ctx.accounts.token_program.to_account_info(), burn_no),
-        params.amount,
-    )?;
-
+    let market_id = market.market_id.to_le_bytes();
+    let seed: &[&[&[u8]]] = &[&[MARKET_SEED, &market_id, &[bump]]];
+
+    let n_outcomes = market.num_outcomes as usize;
+    require!(
+        ctx.remaining_accounts.len() == 2 * n_outcomes,
+        MarketError::InvalidArgument
+    );
+
+    for (outcome, accounts) in ctx.remaining_accounts.chunks(2).enumerate() {
+        let (outcome_mint, trader_outcome) = (&accounts[0], &accounts[1]);
+        require!(
+            market.outcome_mints[outcome] == outcome_mint.key(),
+            MarketError::InvalidOutcome
+        );
+        market.record_burn(outcome as u8, params.amount)?;
+
+        let burn_outcome = Burn {
+            from: trader_outcome.clone(),
+            mint: outcome_mint.clone(),
+            authority: ctx.accounts.trader.to_account_info(),
+        };
+
+        burn(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), burn_outcome),
+            params.amount,
+        )?;
+    }
+
+    let value = ctx.accounts.market.complete_set_value(params.amount)?;
     let transfer_usdc = Transfer {
         from: ctx.accounts.vault_usdc.to_account_info(),
-        to: ctx.accounts.trader.to_account_info(),
+        to: ctx.accounts.trader_usdc.to_account_info(),
         authority: ctx.accounts.market.to_account_info(),
     };
 
@@ -83,8 +95,19 @@ pub fn merge_tokens_handler(ctx: Context<MergeTokens>, params: MergeTokensParams
             transfer_usdc,
             seed,
         ),
-        params.amount,
+        value,
     )?;
 
+    let market = &mut ctx.accounts.market;
+    market.record_complete_set(params.amount, false)?;
+    emit!(TokensMerged {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        trader: ctx.accounts.trader.key(),
+        amount: params.amount,
+    });
+
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
+
     Ok(())
 }
@@ -1,27 +1,71 @@
+pub mod add_outcome;
 pub mod amm;
+pub mod arbitrate_dispute;
+pub mod audit_market;
+pub mod cancel_market;
 pub mod cancel_request;
+pub mod claim_crank_rewards;
+pub mod claim_referral_rewards;
 pub mod claim_rewards;
+pub mod collect_fees;
 pub mod consume_events;
+pub mod correct_resolution;
+pub mod cranker_ledger;
+pub mod create_committee;
 pub mod deposit;
+pub mod dispute_resolution;
+pub mod finalize_resolution;
+pub mod global_config;
 pub mod initialize_market;
 pub mod match_request;
+pub mod merge_open_order;
 pub mod merge_tokens;
 pub mod open_orders;
+pub mod pause_market;
 pub mod place_request;
+pub mod propose_resolution;
+pub mod referrer_ledger;
+pub mod resolve_from_feed;
 pub mod resolve_market;
 pub mod settle_funds;
+pub mod snapshot_feed_price;
 pub mod split_tokens;
+pub mod trader_stats;
+pub mod vote_resolution;
+pub mod vote_rotation;
 
+pub use add_outcome::*;
 pub use amm::*;
+pub use arbitrate_dispute::*;
+pub use audit_market::*;
+pub use cancel_market::*;
 pub use cancel_request::*;
+pub use claim_crank_rewards::*;
+pub use claim_referral_rewards::*;
 pub use claim_rewards::*;
+pub use collect_fees::*;
 pub use consume_events::*;
+pub use correct_resolution::*;
+pub use cranker_ledger::*;
+pub use create_committee::*;
 pub use deposit::*;
+pub use dispute_resolution::*;
+pub use finalize_resolution::*;
+pub use global_config::*;
 pub use initialize_market::*;
 pub use match_request::*;
+pub use merge_open_order::*;
 pub use merge_tokens::*;
 pub use open_orders::*;
+pub use pause_market::*;
 pub use place_request::*;
+pub use propose_resolution::*;
+pub use referrer_ledger::*;
+pub use resolve_from_feed::*;
 pub use resolve_market::*;
 pub use settle_funds::*;
+pub use snapshot_feed_price::*;
 pub use split_tokens::*;
+pub use trader_stats::*;
+pub use vote_resolution::*;
+pub use vote_rotation::*;
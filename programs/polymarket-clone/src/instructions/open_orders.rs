@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 
 use crate::{
-    constants::OPEN_ORDER_SEED,
+    constants::{MAX_OUTCOMES, OPEN_ORDER_SEED},
     state::{Market, OpenOrder},
 };
 
@@ -30,11 +30,12 @@ pub fn open_order_handler(ctx: Context<CreateOpenOrders>) -> Result<()> {
     let open_order = &mut ctx.accounts.open_order;
     open_order.owner = ctx.accounts.owner.key();
     open_order.market = ctx.accounts.market.key();
-    open_order.free_base = 0;
+    open_order.free_base = [0; MAX_OUTCOMES];
     open_order.free_quote = 0;
     open_order.locked_base = 0;
     open_order.locked_quote = 0;
     open_order.slots_bitmaps = 0;
+    open_order.referrer = Pubkey::default();
     open_order.bump = ctx.bumps.open_order;
 
     for i in 0..open_order.slots.len() {
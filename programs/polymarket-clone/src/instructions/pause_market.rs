@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::MarketError,
+    events::MarketStatusChanged,
+    state::{Market, MarketStatus},
+};
+
+#[derive(Accounts)]
+pub struct UpdateMarketStatus<'info> {
+    #[account(mut , has_one = creator @ MarketError::Unauthorized)]
+    pub market: Account<'info, Market>,
+
+    pub creator: Signer<'info>,
+}
+
+pub fn pause_market_handler(ctx: Context<UpdateMarketStatus>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    market.refresh_status(Clock::get()?.unix_timestamp);
+    require!(
+        market.status == MarketStatus::Open,
+        MarketError::InvalidMarketStatus
+    );
+
+    market.status = MarketStatus::Paused;
+
+    emit!(MarketStatusChanged {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        status: market.status,
+    });
+
+    Ok(())
+}
+
+pub fn resume_market_handler(ctx: Context<UpdateMarketStatus>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    require!(
+        market.status == MarketStatus::Paused,
+        MarketError::InvalidMarketStatus
+    );
+
+    // a market paused past its end_ts resumes straight into Closed
+    market.status = MarketStatus::Open;
+    market.refresh_status(Clock::get()?.unix_timestamp);
+
+    emit!(MarketStatusChanged {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        status: market.status,
+    });
+
+    Ok(())
+}
@@ -1,14 +1,14 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
+use anchor_spl::token::{mint_to, transfer, MintTo, Token, TokenAccount, Transfer};
 
 use crate::{
     constants::{
-        OPEN_ORDER_SEED, PRICE_PRECISION_SCALE, REQUEST_QUEUE_SEED, VAULT_NO_SEED, VAULT_USDC_SEED,
-        VAULT_YES_SEED,
+        MARKET_SEED, OPEN_ORDER_SEED, PRICE_PRECISION_SCALE, REQUEST_QUEUE_SEED, VAULT_USDC_SEED,
     },
     error::MarketError,
-    state::{Market, OpenOrder, OrderSide, OutcomeSide, Request, RequestQueue},
-    utils::enqueue_request,
+    events::OrderAccepted,
+    state::{Market, OpenOrder, OrderSide, OrderSlot, ReferrerLedger, Request, RequestQueue},
+    utils::{allocate_order_slot, debug_check_solvency, enqueue_request},
 };
 
 #[repr(C)]
@@ -21,6 +21,9 @@ pub struct PlaceOrderParams {
     pub quantity: u64,
     pub client_id: u64,
     pub outcome: u8,
+    pub auto_split: bool, // sells only: back the order with USDC instead of held outcome tokens
+    // default for none, only recorded on an OpenOrder without one and with the referrer's ledger
+    pub referrer: Pubkey,
 }
 
 #[derive(Accounts)]
@@ -34,11 +37,9 @@ pub struct PlaceOrder<'info> {
     #[account(mut , seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
     pub vault_usdc: Account<'info, TokenAccount>,
 
-    #[account(mut , seeds = [VAULT_YES_SEED , &market.market_id.to_le_bytes()] , bump)]
-    pub vault_yes: Account<'info, TokenAccount>,
-
-    #[account(mut , seeds = [VAULT_NO_SEED , &market.market_id.to_le_bytes()] , bump)]
-    pub vault_no: Account<'info, TokenAccount>,
+    // vault of the outcome being traded, checked against market.outcome_vaults
+    #[account(mut)]
+    pub outcome_vault: Account<'info, TokenAccount>,
 
     #[account(mut , seeds = [OPEN_ORDER_SEED , market.key().as_ref() , owner.key.as_ref()] , bump)]
     pub open_order: Account<'info, OpenOrder>,
@@ -52,11 +53,43 @@ pub struct PlaceOrder<'info> {
     #[account(mut)]
     pub from_outcome: Option<Account<'info, TokenAccount>>,
 
+    // the referrer's ledger on this market, needed when the order records a new referrer
+    pub referrer_ledger: Option<Account<'info, ReferrerLedger>>,
+
     pub token_program: Program<'info, Token>,
 }
 
-pub fn place_order_handler(ctx: Context<PlaceOrder>, params: PlaceOrderParams) -> Result<()> {
+// with auto_split the remaining accounts are (outcome_mint, outcome_vault) for every outcome, in order
+pub fn place_order_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PlaceOrder<'info>>,
+    params: PlaceOrderParams,
+) -> Result<()> {
+    ctx.accounts
+        .market
+        .require_trading(Clock::get()?.unix_timestamp)?;
+    ctx.accounts
+        .market
+        .check_outcome_vault(params.outcome, &ctx.accounts.outcome_vault.key())?;
+
     let open_order = &mut ctx.accounts.open_order;
+    if params.referrer != Pubkey::default() && open_order.referrer == Pubkey::default() {
+        require!(
+            params.referrer != ctx.accounts.owner.key(),
+            MarketError::InvalidArgument
+        );
+        // fills pay the referral share into this ledger, so a referrer without one is refused
+        // here rather than at fill time
+        let ledger = ctx
+            .accounts
+            .referrer_ledger
+            .as_ref()
+            .ok_or(MarketError::ReferrerLedgerMissing)?;
+        require!(
+            ledger.referrer == params.referrer && ledger.market == ctx.accounts.market.key(),
+            MarketError::ReferrerLedgerMissing
+        );
+        open_order.referrer = params.referrer;
+    }
 
     let order_id = Clock::get()?.slot;
 
@@ -64,17 +97,25 @@ pub fn place_order_handler(ctx: Context<PlaceOrder>, params: PlaceOrderParams) -
         return err!(MarketError::InvalidSide);
     }
 
-    if params.price <= 0 || params.quantity <= 0 {
+    if params.price == 0 || params.quantity == 0 {
         return err!(MarketError::InvalidArgument);
     }
 
     if params.side == OrderSide::Buy as u8 {
-        let reserved_quote = params
+        let notional = params
             .price
             .checked_mul(params.quantity as u128)
             .ok_or(MarketError::MathError)?
             .checked_div(PRICE_PRECISION_SCALE)
             .ok_or(MarketError::MathError)? as u64;
+        // the taker fee is reserved up front, whatever isn't charged is returned to free_quote on fill
+        let reserved_quote = notional
+            .checked_add(
+                ctx.accounts
+                    .market
+                    .taker_fee_reserve(notional, params.quantity)?,
+            )
+            .ok_or(MarketError::MathError)?;
 
         if reserved_quote > ctx.accounts.from_usdc.amount {
             return err!(MarketError::InsufficientBalance);
@@ -95,6 +136,68 @@ pub fn place_order_handler(ctx: Context<PlaceOrder>, params: PlaceOrderParams) -
             .locked_quote
             .checked_add(reserved_quote as u128)
             .ok_or(MarketError::MathError)?;
+        ctx.accounts.market.lock_quote(reserved_quote)?;
+    } else if params.auto_split {
+        // shorting in one step: split `quantity` complete sets from USDC, lock the sold outcome
+        // for the order and credit every other outcome to the OpenOrder
+        let n_outcomes = ctx.accounts.market.num_outcomes as usize;
+        require!(
+            ctx.remaining_accounts.len() == 2 * n_outcomes,
+            MarketError::InvalidArgument
+        );
+        if params.quantity > ctx.accounts.from_usdc.amount {
+            return err!(MarketError::InsufficientBalance);
+        }
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.from_usdc.to_account_info(),
+                to: ctx.accounts.vault_usdc.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        );
+        transfer(cpi_ctx, params.quantity)?;
+
+        let bump = ctx.accounts.market.bump;
+        let market_id = ctx.accounts.market.market_id.to_le_bytes();
+        let seeds: &[&[&[u8]]] = &[&[MARKET_SEED, &market_id, &[bump]]];
+
+        for (outcome, accounts) in ctx.remaining_accounts.chunks(2).enumerate() {
+            let (outcome_mint, outcome_vault) = (&accounts[0], &accounts[1]);
+            let market = &mut ctx.accounts.market;
+            require!(
+                market.outcome_mints[outcome] == outcome_mint.key(),
+                MarketError::InvalidOutcome
+            );
+            market.check_outcome_vault(outcome as u8, outcome_vault.key)?;
+            market.record_mint(outcome as u8, params.quantity)?;
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: outcome_mint.clone(),
+                    to: outcome_vault.clone(),
+                    authority: market.to_account_info(),
+                },
+                seeds,
+            );
+            mint_to(cpi_ctx, params.quantity)?;
+
+            if outcome != params.outcome as usize {
+                open_order.free_base[outcome] = open_order.free_base[outcome]
+                    .checked_add(params.quantity as u128)
+                    .ok_or(MarketError::MathError)?;
+            }
+        }
+        ctx.accounts
+            .market
+            .record_complete_set(params.quantity, true)?;
+
+        open_order.locked_base = open_order
+            .locked_base
+            .checked_add(params.quantity as u128)
+            .ok_or(MarketError::MathError)?;
     } else {
         let from_outcome = ctx
             .accounts
@@ -106,19 +209,11 @@ pub fn place_order_handler(ctx: Context<PlaceOrder>, params: PlaceOrderParams) -
             return err!(MarketError::InsufficientBalance);
         }
 
-        let outcome_vault = if params.outcome == OutcomeSide::Yes as u8 {
-            ctx.accounts.vault_yes.to_account_info()
-        } else if params.outcome == OutcomeSide::No as u8 {
-            ctx.accounts.vault_no.to_account_info()
-        } else {
-            return err!(MarketError::InvalidSide);
-        };
-
         let cpi_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
             Transfer {
                 from: from_outcome.to_account_info(),
-                to: outcome_vault,
+                to: ctx.accounts.outcome_vault.to_account_info(),
                 authority: ctx.accounts.owner.to_account_info(),
             },
         );
@@ -130,12 +225,25 @@ pub fn place_order_handler(ctx: Context<PlaceOrder>, params: PlaceOrderParams) -
             .ok_or(MarketError::MathError)?;
     }
 
+    let owner_slot = allocate_order_slot(
+        open_order,
+        OrderSlot {
+            active: true,
+            order_id,
+            price: u64::try_from(params.price).map_err(|_| MarketError::MathError)?,
+            side: params.side,
+            quantity_remaining: params.quantity,
+            outcome: params.outcome,
+        },
+    )?;
+
     let request = Request {
         request_type: params.req_type,
         owner: ctx.accounts.owner.key(),
         side: params.side,
         price: params.price,
         open_order: ctx.accounts.open_order.key(),
+        owner_slot,
         quantity: params.quantity,
         order_id,
         client_id: params.client_id,
@@ -145,5 +253,23 @@ pub fn place_order_handler(ctx: Context<PlaceOrder>, params: PlaceOrderParams) -
 
     enqueue_request(&mut ctx.accounts.request_queue, request)?;
 
+    let market = &mut ctx.accounts.market;
+    emit!(OrderAccepted {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        owner: request.owner,
+        open_order: request.open_order,
+        order_id: request.order_id,
+        client_id: request.client_id,
+        request_type: request.request_type,
+        side: request.side,
+        outcome: request.outcome,
+        price: request.price,
+        quantity: request.quantity,
+        owner_slot: request.owner_slot,
+    });
+
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
+
     Ok(())
 }
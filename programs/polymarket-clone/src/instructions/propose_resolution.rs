@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+
+use crate::{
+    constants::{BOND_VAULT_SEED, PROPOSAL_SEED},
+    error::MarketError,
+    events::ResolutionProposed,
+    state::{Market, MarketStatus, ResolutionMode, ResolutionProposal},
+};
+
+#[derive(Accounts)]
+pub struct ProposeResolution<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + std::mem::size_of::<ResolutionProposal>(),
+        seeds = [PROPOSAL_SEED , market.key().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, ResolutionProposal>,
+
+    #[account(
+        init,
+        payer = proposer,
+        token::mint = usdc_mint,
+        token::authority = market,
+        seeds = [BOND_VAULT_SEED , market.key().as_ref()],
+        bump
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub proposer_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn propose_resolution_handler(ctx: Context<ProposeResolution>, outcome: u8) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let market = &mut ctx.accounts.market;
+    require!(
+        market.resolution_mode == ResolutionMode::Optimistic,
+        MarketError::InvalidResolutionMode
+    );
+    market.refresh_status(now);
+    match market.status {
+        MarketStatus::Closed => {}
+        MarketStatus::Pending | MarketStatus::Open | MarketStatus::Paused => {
+            return err!(MarketError::MarketNotClosed)
+        }
+        _ => return err!(MarketError::InvalidMarketStatus),
+    }
+    require!(outcome < market.num_outcomes, MarketError::InvalidOutcome);
+
+    let bond = market.proposal_bond;
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.proposer_usdc.to_account_info(),
+            to: ctx.accounts.bond_vault.to_account_info(),
+            authority: ctx.accounts.proposer.to_account_info(),
+        },
+    );
+    transfer(cpi_ctx, bond)?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.market = market.key();
+    proposal.proposer = ctx.accounts.proposer.key();
+    proposal.proposed_outcome = outcome;
+    proposal.proposed_at = now;
+    proposal.bond = bond;
+    proposal.disputer = Pubkey::default();
+    proposal.disputed_at = 0;
+    proposal.disputed_outcome = 0;
+    proposal.settled = false;
+    proposal.bump = ctx.bumps.proposal;
+
+    market.status = MarketStatus::Proposed;
+
+    emit!(ResolutionProposed {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        proposer: proposal.proposer,
+        outcome,
+        bond,
+        liveness_ends: now
+            .checked_add(market.liveness_secs)
+            .ok_or(MarketError::MathError)?,
+    });
+
+    Ok(())
+}
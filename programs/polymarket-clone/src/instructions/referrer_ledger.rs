@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::REFERRER_LEDGER_SEED,
+    state::{Market, ReferrerLedger},
+};
+
+#[derive(Accounts)]
+pub struct CreateReferrerLedger<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = referrer,
+        space = 8 + std::mem::size_of::<ReferrerLedger>(),
+        seeds = [REFERRER_LEDGER_SEED , market.key().as_ref() , referrer.key().as_ref()],
+        bump
+    )]
+    pub referrer_ledger: Account<'info, ReferrerLedger>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn referrer_ledger_handler(ctx: Context<CreateReferrerLedger>) -> Result<()> {
+    let referrer_ledger = &mut ctx.accounts.referrer_ledger;
+    referrer_ledger.referrer = ctx.accounts.referrer.key();
+    referrer_ledger.market = ctx.accounts.market.key();
+    referrer_ledger.accrued = 0;
+    referrer_ledger.total_claimed = 0;
+    referrer_ledger.referred_fills = 0;
+    referrer_ledger.referred_volume = 0;
+    referrer_ledger.referred_fees = 0;
+    referrer_ledger.bump = ctx.bumps.referrer_ledger;
+
+    Ok(())
+}
@@ -0,0 +1,331 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::{
+    constants::VAULT_USDC_SEED,
+    error::MarketError,
+    events::MarketResolved,
+    state::{Market, MarketStatus, OutcomeSide, PriceFeed, ResolutionMode},
+    utils::debug_check_solvency,
+};
+
+// permissionless: the outcome is read straight from the price feed fixed at market creation
+#[derive(Accounts)]
+pub struct ResolveFromFeed<'info> {
+    pub resolver: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
+    pub vault_usdc: Account<'info, TokenAccount>,
+
+    /// CHECK: address is pinned to market.price_feed, the owner to the configured oracle program, and
+    /// the data is parsed by PriceFeed::parse
+    #[account(
+        address = market.price_feed @ MarketError::InvalidPriceFeed,
+        owner = market.price_feed_program @ MarketError::InvalidPriceFeed
+    )]
+    pub price_feed: UncheckedAccount<'info>,
+}
+
+pub fn resolve_from_feed_handler(ctx: Context<ResolveFromFeed>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let market = &mut ctx.accounts.market;
+    require!(
+        market.resolution_mode == ResolutionMode::PriceFeed,
+        MarketError::InvalidResolutionMode
+    );
+    market.refresh_status(now);
+    match market.status {
+        MarketStatus::Closed => {}
+        MarketStatus::Pending | MarketStatus::Open | MarketStatus::Paused => {
+            return err!(MarketError::MarketNotClosed)
+        }
+        _ => return err!(MarketError::InvalidMarketStatus),
+    }
+
+    let live = PriceFeed::parse(&ctx.accounts.price_feed.try_borrow_data()?)?;
+    let feed = market.observed_price(live)?;
+    feed.check_confidence(market.max_confidence_bps)?;
+
+    let outcome = if feed.is_above(market.strike_price, market.strike_expo)? {
+        OutcomeSide::Yes as u8
+    } else {
+        OutcomeSide::No as u8
+    };
+    market.apply_resolution(outcome, now)?;
+
+    emit!(MarketResolved {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        resolver: ctx.accounts.resolver.key(),
+        status: market.status,
+        payout_numerators: market.payout_numerators.to_vec(),
+        payout_denominator: market.payout_denominator,
+        claimable_at: market.claims_open_at()?,
+    });
+
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::MAX_OUTCOMES,
+        state::{MarketKind, PRICE_FEED_MAGIC},
+    };
+    use anchor_lang::{InstructionData, ToAccountMetas};
+    use anchor_spl::token::spl_token::state::{Account as SplAccount, AccountState};
+    use solana_program::{
+        clock::Clock,
+        program_error::ProgramError,
+        program_pack::Pack,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+    };
+    use std::sync::{
+        atomic::{AtomicI64, Ordering},
+        Mutex, Once,
+    };
+
+    const END_TS: i64 = 1_000;
+
+    static NOW: AtomicI64 = AtomicI64::new(0);
+    static STUBS: Once = Once::new();
+    // the clock is process wide
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    struct ClockStub;
+
+    impl SyscallStubs for ClockStub {
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let clock = Clock {
+                unix_timestamp: NOW.load(Ordering::SeqCst),
+                ..Clock::default()
+            };
+            unsafe { *(var_addr as *mut Clock) = clock };
+            0
+        }
+    }
+
+    struct Stored {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+    }
+
+    struct FeedMarket {
+        resolver: Stored,
+        market: Stored,
+        vault_usdc: Stored,
+        price_feed: Stored,
+    }
+
+    impl FeedMarket {
+        // a binary market on "price above 100" observed at END_TS with a minute of staleness
+        fn new() -> Self {
+            STUBS.call_once(|| {
+                set_syscall_stubs(Box::new(ClockStub));
+            });
+
+            let zeroed = vec![0u8; std::mem::size_of::<Market>()];
+            let mut market = Market::deserialize(&mut &zeroed[..]).unwrap();
+            market.market_id = 1;
+            market.kind = MarketKind::Binary;
+            market.status = MarketStatus::Open;
+            market.num_outcomes = 2;
+            market.registered_outcomes = 2;
+            market.end_ts = END_TS;
+            market.resolution_mode = ResolutionMode::PriceFeed;
+            market.price_feed = Pubkey::new_unique();
+            market.price_feed_program = Pubkey::new_unique();
+            market.strike_price = 100;
+            market.max_staleness_secs = 60;
+            market.max_confidence_bps = 10_000;
+            market.payout_numerators = [0; MAX_OUTCOMES];
+
+            let mut market_data = vec![];
+            market.try_serialize(&mut market_data).unwrap();
+            market_data.resize(8 + std::mem::size_of::<Market>(), 0);
+
+            let mut vault_data = vec![0u8; SplAccount::LEN];
+            let (vault_key, _) =
+                Pubkey::find_program_address(&[VAULT_USDC_SEED, &1u64.to_le_bytes()], &crate::ID);
+            SplAccount {
+                mint: Pubkey::new_unique(),
+                owner: Pubkey::new_unique(),
+                state: AccountState::Initialized,
+                ..SplAccount::default()
+            }
+            .pack_into_slice(&mut vault_data);
+
+            FeedMarket {
+                resolver: Stored {
+                    key: Pubkey::new_unique(),
+                    owner: Pubkey::default(),
+                    lamports: 1,
+                    data: vec![],
+                },
+                market: Stored {
+                    key: Pubkey::new_unique(),
+                    owner: crate::ID,
+                    lamports: 1,
+                    data: market_data,
+                },
+                vault_usdc: Stored {
+                    key: vault_key,
+                    owner: anchor_spl::token::ID,
+                    lamports: 1,
+                    data: vault_data,
+                },
+                price_feed: Stored {
+                    key: market.price_feed,
+                    owner: market.price_feed_program,
+                    lamports: 1,
+                    data: vec![],
+                },
+            }
+        }
+
+        fn publish(&mut self, price: i64, publish_time: i64) {
+            let data = &mut self.price_feed.data;
+            data.clear();
+            data.extend_from_slice(&PRICE_FEED_MAGIC.to_le_bytes());
+            data.extend_from_slice(&0i32.to_le_bytes());
+            data.extend_from_slice(&price.to_le_bytes());
+            data.extend_from_slice(&0u64.to_le_bytes());
+            data.extend_from_slice(&publish_time.to_le_bytes());
+        }
+
+        fn call(
+            &mut self,
+            metas: Vec<AccountMeta>,
+            data: Vec<u8>,
+            now: i64,
+        ) -> std::result::Result<(), ProgramError> {
+            NOW.store(now, Ordering::SeqCst);
+            let accounts: Vec<AccountInfo> = [
+                &mut self.resolver,
+                &mut self.market,
+                &mut self.vault_usdc,
+                &mut self.price_feed,
+            ]
+            .into_iter()
+            .map(|account| {
+                AccountInfo::new(
+                    &account.key,
+                    false,
+                    false,
+                    &mut account.lamports,
+                    &mut account.data,
+                    &account.owner,
+                    false,
+                    0,
+                )
+            })
+            .collect();
+            let infos: Vec<AccountInfo> = metas
+                .iter()
+                .map(|meta| {
+                    let mut info = accounts
+                        .iter()
+                        .find(|info| *info.key == meta.pubkey)
+                        .unwrap()
+                        .clone();
+                    info.is_signer = meta.is_signer;
+                    info.is_writable = meta.is_writable;
+                    info
+                })
+                .collect();
+            crate::entry(&crate::ID, &infos, &data)
+        }
+
+        fn snapshot(&mut self, now: i64) -> std::result::Result<(), ProgramError> {
+            let metas = crate::accounts::SnapshotFeedPrice {
+                market: self.market.key,
+                price_feed: self.price_feed.key,
+            }
+            .to_account_metas(None);
+            self.call(metas, crate::instruction::SnapshotFeedPrice {}.data(), now)
+        }
+
+        fn resolve(&mut self, now: i64) -> std::result::Result<(), ProgramError> {
+            let metas = crate::accounts::ResolveFromFeed {
+                resolver: self.resolver.key,
+                market: self.market.key,
+                vault_usdc: self.vault_usdc.key,
+                price_feed: self.price_feed.key,
+            }
+            .to_account_metas(None);
+            self.call(metas, crate::instruction::ResolveFromFeed {}.data(), now)
+        }
+
+        fn state(&self) -> Market {
+            Market::try_deserialize(&mut &self.market.data[..]).unwrap()
+        }
+    }
+
+    fn code(error: MarketError) -> std::result::Result<(), ProgramError> {
+        Err(Error::from(error).into())
+    }
+
+    #[test]
+    fn resolves_from_a_live_price_in_the_window() {
+        let _serial = SERIAL
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut m = FeedMarket::new();
+        m.publish(150, END_TS - 10);
+
+        // trading is still open
+        assert_eq!(m.resolve(END_TS - 1), code(MarketError::MarketNotClosed));
+
+        m.resolve(END_TS + 30).unwrap();
+        let market = m.state();
+        assert_eq!(market.status, MarketStatus::ResolvedYes);
+        assert_eq!(market.resolved_at, END_TS + 30);
+        // resolving twice is refused
+        assert_eq!(
+            m.resolve(END_TS + 31),
+            code(MarketError::InvalidMarketStatus)
+        );
+    }
+
+    #[test]
+    fn a_feed_that_moved_on_resolves_from_its_snapshot() {
+        let _serial = SERIAL
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut m = FeedMarket::new();
+
+        // too old to count, the window opens a minute before END_TS
+        m.publish(50, END_TS - 61);
+        assert_eq!(m.snapshot(END_TS - 50), code(MarketError::StalePrice));
+
+        m.publish(50, END_TS - 20);
+        m.snapshot(END_TS - 15).unwrap();
+        assert_eq!(m.snapshot(END_TS - 15), code(MarketError::StalePrice));
+        assert_eq!(m.state().feed_snapshot.price, 50);
+
+        // the live feed keeps publishing past the close, its prints are ignored
+        m.publish(150, END_TS + 5);
+        assert_eq!(m.snapshot(END_TS + 6), code(MarketError::StalePrice));
+        m.resolve(END_TS + 10).unwrap();
+        assert_eq!(m.state().status, MarketStatus::ResolvedNo);
+    }
+
+    #[test]
+    fn a_feed_that_moved_on_without_a_snapshot_is_stale() {
+        let _serial = SERIAL
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut m = FeedMarket::new();
+        m.publish(150, END_TS + 1);
+        assert_eq!(m.resolve(END_TS + 10), code(MarketError::StalePrice));
+        assert_eq!(m.state().status, MarketStatus::Open);
+    }
+}
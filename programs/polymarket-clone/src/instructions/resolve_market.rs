@@ -1,8 +1,12 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
 
 use crate::{
+    constants::VAULT_USDC_SEED,
     error::MarketError,
-    state::{Market, MarketStatus, OutcomeSide},
+    events::MarketResolved,
+    state::{Market, MarketStatus, ResolutionMode},
+    utils::debug_check_solvency,
 };
 
 #[derive(Accounts)]
@@ -10,20 +14,78 @@ pub struct ResolveMarket<'info> {
     #[account(mut)]
     pub market: Account<'info, Market>,
 
+    #[account(seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
+    pub vault_usdc: Account<'info, TokenAccount>,
+
     #[account(mut)]
     pub admin: Signer<'info>,
 }
 
-pub fn resolve_market_handler(ctx: Context<ResolveMarket>, winner: u8) -> Result<()> {
+fn check_resolvable(ctx: &mut Context<ResolveMarket>) -> Result<i64> {
     if ctx.accounts.market.creator != ctx.accounts.admin.key() {
         return err!(MarketError::Unauthorized);
     }
+    require!(
+        ctx.accounts.market.resolution_mode == ResolutionMode::Authority,
+        MarketError::InvalidResolutionMode
+    );
 
-    if winner == OutcomeSide::Yes as u8 {
-        ctx.accounts.market.status = MarketStatus::ResolvedYes
-    } else if winner == OutcomeSide::No as u8 {
-        ctx.accounts.market.status = MarketStatus::ResolvedNo
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.market.refresh_status(now);
+    match ctx.accounts.market.status {
+        MarketStatus::Closed | MarketStatus::Proposed => Ok(now),
+        MarketStatus::Pending | MarketStatus::Open | MarketStatus::Paused => {
+            err!(MarketError::MarketNotClosed)
+        }
+        _ => err!(MarketError::InvalidMarketStatus),
     }
+}
 
+fn emit_resolution(ctx: &mut Context<ResolveMarket>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    emit!(MarketResolved {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        resolver: ctx.accounts.admin.key(),
+        status: market.status,
+        payout_numerators: market.payout_numerators.to_vec(),
+        payout_denominator: market.payout_denominator,
+        claimable_at: market.claims_open_at()?,
+    });
+
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
     Ok(())
 }
+
+pub fn resolve_market_handler(mut ctx: Context<ResolveMarket>, winner: u8) -> Result<()> {
+    let now = check_resolvable(&mut ctx)?;
+
+    ctx.accounts.market.apply_resolution(winner, now)?;
+    emit_resolution(&mut ctx)
+}
+
+pub fn resolve_scalar_handler(mut ctx: Context<ResolveMarket>, value: i64) -> Result<()> {
+    let now = check_resolvable(&mut ctx)?;
+
+    ctx.accounts.market.apply_scalar_resolution(value, now)?;
+    emit_resolution(&mut ctx)
+}
+
+pub fn resolve_with_payouts_handler(
+    mut ctx: Context<ResolveMarket>,
+    payout_numerators: Vec<u64>,
+) -> Result<()> {
+    let now = check_resolvable(&mut ctx)?;
+
+    ctx.accounts
+        .market
+        .apply_payout_vector(&payout_numerators, now)?;
+    emit_resolution(&mut ctx)
+}
+
+pub fn void_market_handler(mut ctx: Context<ResolveMarket>) -> Result<()> {
+    let now = check_resolvable(&mut ctx)?;
+
+    ctx.accounts.market.apply_void(now);
+    emit_resolution(&mut ctx)
+}
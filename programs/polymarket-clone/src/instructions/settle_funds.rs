@@ -2,8 +2,12 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
 
 use crate::{
-    constants::{MARKET_SEED, OPEN_ORDER_SEED},
+    constants::{MARKET_SEED, OPEN_ORDER_SEED, VAULT_USDC_SEED},
+    error::MarketError,
+    events::FundsSettled,
+    instructions::merge_free_sets,
     state::{Market, OpenOrder},
+    utils::debug_check_solvency,
 };
 
 #[derive(Accounts)]
@@ -18,7 +22,7 @@ pub struct SettleFunds<'info> {
     #[account(mut , seeds = [OPEN_ORDER_SEED , market.key().as_ref() , owner.key().as_ref()] , bump)]
     pub open_order: Account<'info, OpenOrder>,
 
-    #[account(mut)]
+    #[account(mut , seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
     pub vault_usdc: Account<'info, TokenAccount>,
 
     #[account(mut)]
@@ -27,34 +31,95 @@ pub struct SettleFunds<'info> {
     pub token_program: Program<'info, Token>,
 }
 
-pub fn settle_funds_handler(ctx: Context<SettleFunds>) -> Result<()> {
+// remaining accounts, optional: (outcome_mint, outcome_vault, owner_outcome_account) for every
+// outcome, in order, to also withdraw the outcome tokens credited to the OpenOrder; with auto_merge
+// the complete sets among them are merged into free_quote first
+pub fn settle_funds_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SettleFunds<'info>>,
+    auto_merge: bool,
+) -> Result<()> {
+    let n_outcomes = ctx.accounts.market.num_outcomes as usize;
+    require!(
+        ctx.remaining_accounts.is_empty() || ctx.remaining_accounts.len() == 3 * n_outcomes,
+        MarketError::InvalidArgument
+    );
+
+    if auto_merge {
+        merge_free_sets(
+            &mut ctx.accounts.market,
+            &mut ctx.accounts.open_order,
+            ctx.remaining_accounts,
+            3,
+            &ctx.accounts.token_program.to_account_info(),
+        )?;
+    }
+
+    let bump = ctx.accounts.market.bump;
+    let market_id = ctx.accounts.market.market_id.to_le_bytes();
+    let seeds: &[&[&[u8]]] = &[&[MARKET_SEED, &market_id, &[bump]]];
+
     let open_order = &mut ctx.accounts.open_order;
-    let free_quote = open_order.free_quote;
-    if free_quote == 0 {
+    let mut base_amount = 0u64;
+    for (outcome, accounts) in ctx.remaining_accounts.chunks(3).enumerate() {
+        let (outcome_vault, owner_outcome) = (&accounts[1], &accounts[2]);
+        let free_base = open_order.free_base[outcome] as u64;
+        if free_base == 0 {
+            continue;
+        }
+        ctx.accounts
+            .market
+            .check_outcome_vault(outcome as u8, outcome_vault.key)?;
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: outcome_vault.clone(),
+                to: owner_outcome.clone(),
+                authority: ctx.accounts.market.to_account_info(),
+            },
+            seeds,
+        );
+        transfer(cpi_ctx, free_base)?;
+
+        open_order.free_base[outcome] = 0;
+        base_amount = base_amount
+            .checked_add(free_base)
+            .ok_or(MarketError::MathError)?;
+    }
+
+    let amount = open_order.free_quote as u64;
+    if amount == 0 && base_amount == 0 {
         return Ok(());
     }
-    let amount = free_quote as u64;
 
-    let bump = ctx.accounts.market.bump;
-    let seeds: &[&[&[u8]]] = &[&[
-        MARKET_SEED,
-        &ctx.accounts.market.market_id.to_le_bytes(),
-        &[bump],
-    ]];
-
-    let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.vault_usdc.to_account_info(),
-            to: ctx.accounts.owner_usdc.to_account_info(),
-            authority: ctx.accounts.market.to_account_info(),
-        },
-        seeds,
-    );
+    if amount > 0 {
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_usdc.to_account_info(),
+                to: ctx.accounts.owner_usdc.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            },
+            seeds,
+        );
+
+        transfer(cpi_ctx, amount)?;
+
+        open_order.free_quote = 0;
+    }
 
-    transfer(cpi_ctx, amount)?;
+    let market = &mut ctx.accounts.market;
+    market.release_quote(amount)?;
+    emit!(FundsSettled {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        owner: ctx.accounts.owner.key(),
+        open_order: open_order.key(),
+        amount,
+        base_amount,
+    });
 
-    open_order.free_quote = 0;
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
 
     Ok(())
 }
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::MarketError,
+    events::FeedPriceSnapshotted,
+    state::{Market, PriceFeed, ResolutionMode},
+};
+
+// permissionless: records the feed's price while it still shows one from the observation window,
+// so a live feed that keeps publishing past end_ts can still resolve the market
+#[derive(Accounts)]
+pub struct SnapshotFeedPrice<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: pinned exactly like in resolve_from_feed and parsed by PriceFeed::parse
+    #[account(
+        address = market.price_feed @ MarketError::InvalidPriceFeed,
+        owner = market.price_feed_program @ MarketError::InvalidPriceFeed
+    )]
+    pub price_feed: UncheckedAccount<'info>,
+}
+
+pub fn snapshot_feed_price_handler(ctx: Context<SnapshotFeedPrice>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    require!(
+        market.resolution_mode == ResolutionMode::PriceFeed,
+        MarketError::InvalidResolutionMode
+    );
+    require!(!market.is_resolved(), MarketError::InvalidMarketStatus);
+
+    let feed = PriceFeed::parse(&ctx.accounts.price_feed.try_borrow_data()?)?;
+    feed.check_staleness(market.end_ts, market.max_staleness_secs)?;
+    // only ever moves towards end_ts, an older print can't replace a newer one
+    require!(
+        feed.publish_time > market.feed_snapshot.publish_time,
+        MarketError::StalePrice
+    );
+    market.feed_snapshot = feed;
+
+    emit!(FeedPriceSnapshotted {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        price: feed.price,
+        expo: feed.expo,
+        conf: feed.conf,
+        publish_time: feed.publish_time,
+    });
+
+    Ok(())
+}
@@ -1,32 +1,31 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{mint_to, transfer, Mint, MintTo, Token, TokenAccount, Transfer};
+use anchor_spl::token::{mint_to, transfer, MintTo, Token, TokenAccount, Transfer};
 
-use crate::{constants::VAULT_YES_SEED, state::Market};
+use crate::{
+    constants::{MARKET_SEED, VAULT_USDC_SEED},
+    error::MarketError,
+    events::TokensSplit,
+    state::Market,
+    utils::debug_check_solvency,
+};
 
 #[derive(Accounts)]
-#[instruction(params: SplitOrderParams)]
 pub struct SplitToken<'info> {
-    #[account(signer)]
     pub trader: Signer<'info>,
 
-    #[account(mut)]
+    #[account(mut , has_one = vault_usdc @ MarketError::InvalidArgument)]
     pub market: Account<'info, Market>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        token::mint = market.usdc_mint,
+        token::authority = trader
+    )]
     pub trader_usdc: Account<'info, TokenAccount>,
 
-    #[account(mut)]
-    pub trader_yes: Account<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub trader_no: Account<'info, TokenAccount>,
-
-    #[account(mut ,  seeds = [VAULT_YES_SEED , &params.market_id.to_le_bytes()] , bump)]
+    #[account(mut , seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
     pub vault_usdc: Account<'info, TokenAccount>,
 
-    pub yes_mint: Account<'info, Mint>,
-    pub no_mint: Account<'info, Mint>,
-
     pub token_program: Program<'info, Token>,
 }
 
@@ -34,11 +33,25 @@ pub struct SplitToken<'info> {
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 
 pub struct SplitOrderParams {
-    amount: u64,
-    market_id: u64,
+    pub amount: u64,
 }
 
-pub fn split_tokens_handler(ctx: Context<SplitToken>, params: SplitOrderParams) -> Result<()> {
+// remaining accounts: (outcome_mint, trader_outcome_account) for every outcome, in outcome order
+pub fn split_tokens_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SplitToken<'info>>,
+    params: SplitOrderParams,
+) -> Result<()> {
+    ctx.accounts
+        .market
+        .require_trading(Clock::get()?.unix_timestamp)?;
+    require!(params.amount > 0, MarketError::InvalidArgument);
+
+    let n_outcomes = ctx.accounts.market.num_outcomes as usize;
+    require!(
+        ctx.remaining_accounts.len() == 2 * n_outcomes,
+        MarketError::InvalidArgument
+    );
+
     let transfer_ix = Transfer {
         from: ctx.accounts.trader_usdc.to_account_info(),
         to: ctx.accounts.vault_usdc.to_account_info(),
@@ -50,27 +63,47 @@ pub fn split_tokens_handler(ctx: Context<SplitToken>, params: SplitOrderParams)
         params.amount,
     )?;
 
-    let mint_yes = MintTo {
-        mint: ctx.accounts.yes_mint.to_account_info(),
-        to: ctx.accounts.trader_yes.to_account_info(),
-        authority: ctx.accounts.market.to_account_info(),
-    };
-
-    mint_to(
-        CpiContext::new(ctx.accounts.token_program.to_account_info(), mint_yes),
-        params.amount,
-    )?;
-
-    let mint_no = MintTo {
-        mint: ctx.accounts.no_mint.to_account_info(),
-        to: ctx.accounts.trader_no.to_account_info(),
-        authority: ctx.accounts.market.to_account_info(),
-    };
-
-    mint_to(
-        CpiContext::new(ctx.accounts.token_program.to_account_info(), mint_no),
-        params.amount,
-    )?;
+    let bump = ctx.accounts.market.bump;
+    let market_id = ctx.accounts.market.market_id.to_le_bytes();
+    let seeds: &[&[&[u8]]] = &[&[MARKET_SEED, &market_id, &[bump]]];
+
+    // one of every outcome per USDC deposited, minted under the market PDA
+    for (outcome, accounts) in ctx.remaining_accounts.chunks(2).enumerate() {
+        ctx.accounts
+            .market
+            .record_mint(outcome as u8, params.amount)?;
+        let (outcome_mint, trader_outcome) = (&accounts[0], &accounts[1]);
+        require!(
+            ctx.accounts.market.outcome_mints[outcome] == outcome_mint.key(),
+            MarketError::InvalidOutcome
+        );
+
+        let mint_outcome = MintTo {
+            mint: outcome_mint.clone(),
+            to: trader_outcome.clone(),
+            authority: ctx.accounts.market.to_account_info(),
+        };
+
+        mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                mint_outcome,
+                seeds,
+            ),
+            params.amount,
+        )?;
+    }
+
+    let market = &mut ctx.accounts.market;
+    market.record_complete_set(params.amount, true)?;
+    emit!(TokensSplit {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        trader: ctx.accounts.trader.key(),
+        amount: params.amount,
+    });
+
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
 
     Ok(())
 }
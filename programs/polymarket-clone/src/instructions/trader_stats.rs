@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{TRADER_STATS_SEED, VOLUME_WINDOW_DAYS},
+    state::TraderStats,
+};
+
+#[derive(Accounts)]
+pub struct CreateTraderStats<'info> {
+    #[account(mut)]
+    pub trader: Signer<'info>,
+
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + std::mem::size_of::<TraderStats>(),
+        seeds = [TRADER_STATS_SEED , trader.key().as_ref()],
+        bump
+    )]
+    pub trader_stats: Account<'info, TraderStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn trader_stats_handler(ctx: Context<CreateTraderStats>) -> Result<()> {
+    let trader_stats = &mut ctx.accounts.trader_stats;
+    trader_stats.trader = ctx.accounts.trader.key();
+    trader_stats.daily_notional = [0; VOLUME_WINDOW_DAYS];
+    trader_stats.last_day = 0;
+    trader_stats.lifetime_notional = 0;
+    trader_stats.bump = ctx.bumps.trader_stats;
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::{
+    constants::{COMMITTEE_SEED, VAULT_USDC_SEED},
+    error::MarketError,
+    events::{MarketResolved, ResolutionVoteCast},
+    state::{Market, MarketStatus, ResolutionMode, ResolverCommittee},
+    utils::debug_check_solvency,
+};
+
+#[derive(Accounts)]
+pub struct VoteResolution<'info> {
+    pub member: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(seeds = [VAULT_USDC_SEED , &market.market_id.to_le_bytes()] , bump)]
+    pub vault_usdc: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [COMMITTEE_SEED , market.key().as_ref()],
+        bump = committee.bump,
+        has_one = market @ MarketError::InvalidArgument
+    )]
+    pub committee: Account<'info, ResolverCommittee>,
+}
+
+// members may change their vote until some outcome collects `threshold` votes, which resolves the market
+pub fn vote_resolution_handler(ctx: Context<VoteResolution>, outcome: u8) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let market = &mut ctx.accounts.market;
+    require!(
+        market.resolution_mode == ResolutionMode::Committee,
+        MarketError::InvalidResolutionMode
+    );
+    market.refresh_status(now);
+    match market.status {
+        MarketStatus::Closed => {}
+        MarketStatus::Pending | MarketStatus::Open | MarketStatus::Paused => {
+            return err!(MarketError::MarketNotClosed)
+        }
+        _ => return err!(MarketError::InvalidMarketStatus),
+    }
+    require!(outcome < market.num_outcomes, MarketError::InvalidOutcome);
+
+    let committee = &mut ctx.accounts.committee;
+    let member_index = committee.member_index(&ctx.accounts.member.key())?;
+    committee.votes[member_index] = outcome;
+    let votes = committee.votes_for(outcome);
+
+    emit!(ResolutionVoteCast {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        member: ctx.accounts.member.key(),
+        outcome,
+        votes,
+        threshold: committee.threshold,
+    });
+
+    if votes >= committee.threshold {
+        market.apply_resolution(outcome, now)?;
+
+        emit!(MarketResolved {
+            seq: market.next_event_seq()?,
+            market: market.key(),
+            resolver: committee.key(),
+            status: market.status,
+            payout_numerators: market.payout_numerators.to_vec(),
+            payout_denominator: market.payout_denominator,
+            claimable_at: market.claims_open_at()?,
+        });
+    }
+
+    debug_check_solvency(&mut ctx.accounts.market, &mut ctx.accounts.vault_usdc)?;
+
+    Ok(())
+}
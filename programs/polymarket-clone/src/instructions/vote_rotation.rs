@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{COMMITTEE_SEED, ROTATION_PROPOSAL_TTL_SECS},
+    error::MarketError,
+    events::CommitteeRotated,
+    state::{Market, ResolverCommittee},
+};
+
+#[derive(Accounts)]
+pub struct VoteRotation<'info> {
+    pub member: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [COMMITTEE_SEED , market.key().as_ref()],
+        bump = committee.bump,
+        has_one = market @ MarketError::InvalidArgument
+    )]
+    pub committee: Account<'info, ResolverCommittee>,
+}
+
+// approving the pending member set adds the caller's approval; a different set can only be proposed
+// once the pending one has expired, so one member can't keep wiping the others' approvals. Once
+// `threshold` current members approve, the new set takes over and outstanding votes are cleared
+pub fn vote_rotation_handler(
+    ctx: Context<VoteRotation>,
+    members: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    require!(!market.is_resolved(), MarketError::InvalidMarketStatus);
+    ResolverCommittee::validate_members(&members, threshold)?;
+
+    let committee = &mut ctx.accounts.committee;
+    let member_index = committee.member_index(&ctx.accounts.member.key())?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let expired = now >= committee.rotation_expires_at;
+    if expired || !committee.is_pending_rotation(&members, threshold) {
+        require!(expired, MarketError::RotationPending);
+        committee.clear_rotation();
+        committee.rotation_expires_at = now
+            .checked_add(ROTATION_PROPOSAL_TTL_SECS)
+            .ok_or(MarketError::MathError)?;
+        committee.pending_members[..members.len()].copy_from_slice(&members);
+        committee.pending_member_count = members.len() as u8;
+        committee.pending_threshold = threshold;
+    }
+    committee.rotation_approvals |= 1 << member_index;
+
+    if committee.rotation_approvals.count_ones() >= committee.threshold as u32 {
+        committee.set_members(&members, threshold);
+
+        emit!(CommitteeRotated {
+            seq: market.next_event_seq()?,
+            market: market.key(),
+            members,
+            threshold,
+        });
+    }
+
+    Ok(())
+}
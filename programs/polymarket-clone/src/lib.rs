@@ -2,10 +2,13 @@ use anchor_lang::prelude::*;
 
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod state;
 pub mod utils;
+use crate::events::CrankProgress;
 use crate::instructions::*;
+use crate::state::FeeTier;
 declare_id!("2j64V9Te3wcmWZnkZDDSd3iA5YYfwErPAGeD9ip7i5BD");
 
 #[program]
@@ -19,7 +22,29 @@ pub mod polymarket_clone {
         initial_market_handler(ctx, params)
     }
 
-    pub fn place_order_request(ctx: Context<PlaceOrder>, params: PlaceOrderParams) -> Result<()> {
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        fee_tiers: Vec<FeeTier>,
+    ) -> Result<()> {
+        initialize_config_handler(ctx, fee_tiers)
+    }
+
+    pub fn set_fee_tiers(ctx: Context<UpdateConfig>, fee_tiers: Vec<FeeTier>) -> Result<()> {
+        set_fee_tiers_handler(ctx, fee_tiers)
+    }
+
+    pub fn set_fee_split(ctx: Context<UpdateConfig>, params: FeeSplitParams) -> Result<()> {
+        set_fee_split_handler(ctx, params)
+    }
+
+    pub fn add_outcome(ctx: Context<AddOutcome>) -> Result<()> {
+        add_outcome_handler(ctx)
+    }
+
+    pub fn place_order_request<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceOrder<'info>>,
+        params: PlaceOrderParams,
+    ) -> Result<()> {
         place_order_handler(ctx, params)
     }
 
@@ -35,34 +60,162 @@ pub mod polymarket_clone {
         cancel_order_handler(ctx, order_id)
     }
 
-    pub fn match_request(ctx: Context<MatchRequest>, max_requests: u16) -> Result<()> {
+    pub fn match_request(ctx: Context<MatchRequest>, max_requests: u16) -> Result<CrankProgress> {
         match_order_handler(ctx, max_requests)
     }
 
     pub fn consume_events<'info>(
         ctx: Context<'_, '_, 'info, 'info, ConsumeEvents<'info>>,
         max_events: u16,
-    ) -> Result<()> {
+    ) -> Result<CrankProgress> {
         consume_events_handler(ctx, max_events)
     }
 
-    pub fn settle_funds(ctx: Context<SettleFunds>) -> Result<()> {
-        settle_funds_handler(ctx)
+    pub fn create_cranker_ledger(ctx: Context<CreateCrankerLedger>) -> Result<()> {
+        cranker_ledger_handler(ctx)
+    }
+
+    pub fn create_trader_stats(ctx: Context<CreateTraderStats>) -> Result<()> {
+        trader_stats_handler(ctx)
+    }
+
+    pub fn claim_crank_rewards(ctx: Context<ClaimCrankRewards>) -> Result<()> {
+        claim_crank_rewards_handler(ctx)
+    }
+
+    pub fn create_referrer_ledger(ctx: Context<CreateReferrerLedger>) -> Result<()> {
+        referrer_ledger_handler(ctx)
+    }
+
+    pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+        claim_referral_rewards_handler(ctx)
+    }
+
+    pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+        collect_fees_handler(ctx)
+    }
+
+    pub fn settle_funds<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleFunds<'info>>,
+        auto_merge: bool,
+    ) -> Result<()> {
+        settle_funds_handler(ctx, auto_merge)
+    }
+
+    pub fn merge_open_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MergeOpenOrder<'info>>,
+    ) -> Result<()> {
+        merge_open_order_handler(ctx)
     }
 
     pub fn resolve_market(ctx: Context<ResolveMarket>, winner: u8) -> Result<()> {
         resolve_market_handler(ctx, winner)
     }
 
-    pub fn claim_reward(ctx: Context<ClaimReward>, amount: u64) -> Result<()> {
-        claim_reward_handler(ctx, amount)
+    pub fn resolve_with_payouts(
+        ctx: Context<ResolveMarket>,
+        payout_numerators: Vec<u64>,
+    ) -> Result<()> {
+        resolve_with_payouts_handler(ctx, payout_numerators)
+    }
+
+    pub fn void_market(ctx: Context<ResolveMarket>) -> Result<()> {
+        void_market_handler(ctx)
+    }
+
+    pub fn correct_resolution(
+        ctx: Context<CorrectResolution>,
+        payout_numerators: Vec<u64>,
+    ) -> Result<()> {
+        correct_resolution_handler(ctx, payout_numerators)
+    }
+
+    pub fn cancel_market<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelMarket<'info>>,
+    ) -> Result<()> {
+        cancel_market_handler(ctx)
+    }
+
+    pub fn audit_market<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AuditMarket<'info>>,
+    ) -> Result<()> {
+        audit_market_handler(ctx)
+    }
+
+    pub fn pause_market(ctx: Context<UpdateMarketStatus>) -> Result<()> {
+        pause_market_handler(ctx)
+    }
+
+    pub fn resume_market(ctx: Context<UpdateMarketStatus>) -> Result<()> {
+        resume_market_handler(ctx)
+    }
+
+    pub fn resolve_scalar_market(ctx: Context<ResolveMarket>, value: i64) -> Result<()> {
+        resolve_scalar_handler(ctx, value)
+    }
+
+    pub fn propose_resolution(ctx: Context<ProposeResolution>, outcome: u8) -> Result<()> {
+        propose_resolution_handler(ctx, outcome)
+    }
+
+    pub fn dispute_resolution(ctx: Context<DisputeResolution>, outcome: u8) -> Result<()> {
+        dispute_resolution_handler(ctx, outcome)
+    }
+
+    pub fn finalize_resolution(ctx: Context<FinalizeResolution>) -> Result<()> {
+        finalize_resolution_handler(ctx)
+    }
+
+    pub fn arbitrate_dispute(ctx: Context<ArbitrateDispute>, outcome: u8) -> Result<()> {
+        arbitrate_dispute_handler(ctx, outcome)
     }
 
-    pub fn split_tokens(ctx: Context<SplitToken>, params: SplitOrderParams) -> Result<()> {
+    pub fn snapshot_feed_price(ctx: Context<SnapshotFeedPrice>) -> Result<()> {
+        snapshot_feed_price_handler(ctx)
+    }
+
+    pub fn resolve_from_feed(ctx: Context<ResolveFromFeed>) -> Result<()> {
+        resolve_from_feed_handler(ctx)
+    }
+
+    pub fn create_committee(
+        ctx: Context<CreateCommittee>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        create_committee_handler(ctx, members, threshold)
+    }
+
+    pub fn vote_resolution(ctx: Context<VoteResolution>, outcome: u8) -> Result<()> {
+        vote_resolution_handler(ctx, outcome)
+    }
+
+    pub fn vote_rotation(
+        ctx: Context<VoteRotation>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        vote_rotation_handler(ctx, members, threshold)
+    }
+
+    pub fn claim_reward<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimReward<'info>>,
+        params: ClaimRewardParams,
+    ) -> Result<()> {
+        claim_reward_handler(ctx, params)
+    }
+
+    pub fn split_tokens<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SplitToken<'info>>,
+        params: SplitOrderParams,
+    ) -> Result<()> {
         split_tokens_handler(ctx, params)
     }
 
-    pub fn merge_tokens(ctx: Context<MergeTokens>, params: MergeTokensParams) -> Result<()> {
+    pub fn merge_tokens<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MergeTokens<'info>>,
+        params: MergeTokensParams,
+    ) -> Result<()> {
         merge_tokens_handler(ctx, params)
     }
 }
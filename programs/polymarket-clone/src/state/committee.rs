@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::MAX_COMMITTEE_MEMBERS, error::MarketError};
+
+pub const NO_VOTE: u8 = u8::MAX;
+
+#[account]
+pub struct ResolverCommittee {
+    pub market: Pubkey,
+    pub members: [Pubkey; MAX_COMMITTEE_MEMBERS],
+    pub member_count: u8,
+    pub threshold: u8,
+    pub votes: [u8; MAX_COMMITTEE_MEMBERS], // outcome each member voted for, NO_VOTE if none
+    pub pending_members: [Pubkey; MAX_COMMITTEE_MEMBERS],
+    pub pending_member_count: u8,
+    pub pending_threshold: u8,
+    pub rotation_approvals: u16,  // bitmap over the current members
+    pub rotation_expires_at: i64, // until then the pending set can only be approved, not replaced
+    pub bump: u8,
+}
+
+impl ResolverCommittee {
+    pub fn validate_members(members: &[Pubkey], threshold: u8) -> Result<()> {
+        require!(
+            !members.is_empty() && members.len() <= MAX_COMMITTEE_MEMBERS,
+            MarketError::InvalidCommittee
+        );
+        require!(
+            threshold > 0 && threshold as usize <= members.len(),
+            MarketError::InvalidCommittee
+        );
+        for (i, member) in members.iter().enumerate() {
+            require!(
+                *member != Pubkey::default() && !members[..i].contains(member),
+                MarketError::InvalidCommittee
+            );
+        }
+        Ok(())
+    }
+
+    pub fn set_members(&mut self, members: &[Pubkey], threshold: u8) {
+        self.members = [Pubkey::default(); MAX_COMMITTEE_MEMBERS];
+        self.members[..members.len()].copy_from_slice(members);
+        self.member_count = members.len() as u8;
+        self.threshold = threshold;
+        self.votes = [NO_VOTE; MAX_COMMITTEE_MEMBERS];
+        self.clear_rotation();
+    }
+
+    pub fn clear_rotation(&mut self) {
+        self.pending_members = [Pubkey::default(); MAX_COMMITTEE_MEMBERS];
+        self.pending_member_count = 0;
+        self.pending_threshold = 0;
+        self.rotation_approvals = 0;
+        self.rotation_expires_at = 0;
+    }
+
+    pub fn member_index(&self, key: &Pubkey) -> Result<usize> {
+        self.members[..self.member_count as usize]
+            .iter()
+            .position(|member| member == key)
+            .ok_or(error!(MarketError::NotCommitteeMember))
+    }
+
+    pub fn votes_for(&self, outcome: u8) -> u8 {
+        self.votes[..self.member_count as usize]
+            .iter()
+            .filter(|vote| **vote == outcome)
+            .count() as u8
+    }
+
+    pub fn is_pending_rotation(&self, members: &[Pubkey], threshold: u8) -> bool {
+        self.pending_member_count as usize == members.len()
+            && self.pending_threshold == threshold
+            && self.pending_members[..members.len()] == *members
+    }
+}
@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct CrankerLedger {
+    pub cranker: Pubkey,
+    pub market: Pubkey,
+    pub accrued: u64,
+    pub total_claimed: u64,
+    pub requests_matched: u64,
+    pub events_consumed: u64,
+    pub bump: u8,
+}
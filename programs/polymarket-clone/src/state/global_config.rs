@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::MAX_FEE_TIERS, error::MarketError, state::Market};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct FeeTier {
+    pub min_volume: u64, // rolling 30 day taker notional, in USDC base units
+    pub taker_fee_bps: u16,
+}
+
+#[account]
+pub struct GlobalConfig {
+    pub admin: Pubkey,
+    pub fee_tiers: [FeeTier; MAX_FEE_TIERS],
+    pub tier_count: u8,
+    pub fee_authority: Pubkey, // the only signer allowed to run collect_fees
+    pub treasury: Pubkey,      // owner of the protocol's USDC account
+    pub insurance_fund: Pubkey, // owner of the insurance bucket's USDC account
+    pub protocol_share_bps: u16,
+    pub creator_share_bps: u16,
+    pub insurance_share_bps: u16,
+    pub referral_share_bps: u16, // of the taker fee, paid to the taker's referrer before the split
+    pub bump: u8,
+}
+
+impl GlobalConfig {
+    // tiers climb in volume and only ever get cheaper
+    pub fn validate_tiers(tiers: &[FeeTier]) -> Result<()> {
+        require!(
+            tiers.len() <= MAX_FEE_TIERS,
+            MarketError::InvalidFeeSchedule
+        );
+        for pair in tiers.windows(2) {
+            require!(
+                pair[0].min_volume < pair[1].min_volume
+                    && pair[0].taker_fee_bps >= pair[1].taker_fee_bps,
+                MarketError::InvalidFeeSchedule
+            );
+        }
+        Ok(())
+    }
+
+    pub fn validate_split(protocol_bps: u16, creator_bps: u16, insurance_bps: u16) -> Result<()> {
+        require!(
+            protocol_bps as u32 + creator_bps as u32 + insurance_bps as u32 == 10_000,
+            MarketError::InvalidFeeSchedule
+        );
+        Ok(())
+    }
+
+    // (protocol, creator, insurance) shares of `amount`, the protocol keeps the rounding dust
+    pub fn split_fees(&self, amount: u64) -> Result<(u64, u64, u64)> {
+        let creator = Market::fee_on(amount, self.creator_share_bps)?;
+        let insurance = Market::fee_on(amount, self.insurance_share_bps)?;
+        let protocol = amount
+            .checked_sub(creator)
+            .and_then(|rest| rest.checked_sub(insurance))
+            .ok_or(MarketError::MathError)?;
+        Ok((protocol, creator, insurance))
+    }
+
+    pub fn set_tiers(&mut self, tiers: &[FeeTier]) {
+        self.fee_tiers = [FeeTier::default(); MAX_FEE_TIERS];
+        self.fee_tiers[..tiers.len()].copy_from_slice(tiers);
+        self.tier_count = tiers.len() as u8;
+    }
+
+    // a tier never raises a market's taker rate: buys reserve the market rate when placed and the
+    // discount comes back to free_quote with the rest of the unused reserve when they fill
+    pub fn taker_fee_bps(&self, market_bps: u16, volume: u64) -> u16 {
+        self.fee_tiers[..self.tier_count as usize]
+            .iter()
+            .rev()
+            .find(|tier| volume >= tier.min_volume)
+            .map_or(market_bps, |tier| tier.taker_fee_bps.min(market_bps))
+    }
+}
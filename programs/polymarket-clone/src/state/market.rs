@@ -1,14 +1,105 @@
 use anchor_lang::prelude::*;
 
+use crate::{
+    constants::{MAX_OUTCOMES, PRICE_PRECISION_SCALE},
+    error::MarketError,
+    state::{OutcomeSide, PriceFeed},
+};
+
+// Pending -> Open -> Closed (at end_ts) -> Proposed -> ResolvedYes / ResolvedNo / Resolved / Cancelled
+// Pending only opens once every outcome mint has been registered
+// Open <-> Paused is an admin switch while trading
 #[repr(u8)]
 #[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 
 pub enum MarketStatus {
-    Open = 0,
-    Paused = 1,
-    ResolvedYes = 2,
-    ResolvedNo = 3,
-    Cancelled = 4,
+    Pending = 0,
+    Open = 1,
+    Paused = 2,
+    Closed = 3,
+    Proposed = 4,
+    ResolvedYes = 5,
+    ResolvedNo = 6,
+    Cancelled = 7, // voided, every outcome redeems for an equal share
+    Resolved = 8, // scalar, categorical and payout vector resolutions, the payout numerators say what each outcome receives
+}
+
+#[repr(u8)]
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+
+pub enum MarketKind {
+    Binary = 0,      // outcome 0 is YES, outcome 1 is NO
+    Scalar = 1,      // outcome 0 is LONG, outcome 1 is SHORT, payout follows the resolved value
+    Categorical = 2, // num_outcomes mutually exclusive outcomes, exactly one of them wins
+}
+
+impl MarketKind {
+    pub fn from_u8(kind: u8) -> Result<Self> {
+        match kind {
+            0 => Ok(MarketKind::Binary),
+            1 => Ok(MarketKind::Scalar),
+            2 => Ok(MarketKind::Categorical),
+            _ => err!(MarketError::InvalidArgument),
+        }
+    }
+}
+
+#[repr(u8)]
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+
+pub enum ResolutionMode {
+    Authority = 0,  // market.oracle sets the outcome directly
+    Optimistic = 1, // bonded proposal, liveness window, disputes arbitrated by market.oracle
+    PriceFeed = 2,  // YES when the feed price at end_ts is above the strike, no human involved
+    Committee = 3,  // M of N members of the market's ResolverCommittee vote for the winner
+}
+
+impl ResolutionMode {
+    pub fn from_u8(mode: u8) -> Result<Self> {
+        match mode {
+            0 => Ok(ResolutionMode::Authority),
+            1 => Ok(ResolutionMode::Optimistic),
+            2 => Ok(ResolutionMode::PriceFeed),
+            3 => Ok(ResolutionMode::Committee),
+            _ => err!(MarketError::InvalidArgument),
+        }
+    }
+}
+
+#[repr(u8)]
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+
+pub enum RefundMode {
+    Even = 0,        // every outcome refunds 1 / num_outcomes
+    NetDeposits = 1, // each outcome refunds the collateral deposited for it, spread over its supply
+}
+
+impl RefundMode {
+    pub fn from_u8(mode: u8) -> Result<Self> {
+        match mode {
+            0 => Ok(RefundMode::Even),
+            1 => Ok(RefundMode::NetDeposits),
+            _ => err!(MarketError::InvalidArgument),
+        }
+    }
+}
+
+#[repr(u8)]
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+
+pub enum FeeCurve {
+    Flat = 0,        // fee bps apply to the notional, p * quantity
+    Probability = 1, // fee bps apply to min(p, 1 - p) * quantity, cheapest at the extremes
+}
+
+impl FeeCurve {
+    pub fn from_u8(curve: u8) -> Result<Self> {
+        match curve {
+            0 => Ok(FeeCurve::Flat),
+            1 => Ok(FeeCurve::Probability),
+            _ => err!(MarketError::InvalidArgument),
+        }
+    }
 }
 
 #[account]
@@ -17,24 +108,410 @@ pub struct Market {
     pub creator: Pubkey,
     pub question: String,
     pub description: String,
+    pub kind: MarketKind,
+    pub scalar_lower: i64,
+    pub scalar_upper: i64,
+    pub start_ts: i64,
     pub end_ts: i64,
     pub status: MarketStatus,
-    pub yes_mint: Pubkey,
-    pub no_mint: Pubkey,
+    pub num_outcomes: u8,
+    pub registered_outcomes: u8, // outcomes past the first two are added one by one before trading starts
+    pub outcome_mints: [Pubkey; MAX_OUTCOMES],
+    pub outcome_vaults: [Pubkey; MAX_OUTCOMES],
     pub usdc_mint: Pubkey,
-    pub vault_yes: Pubkey,
-    pub vault_no: Pubkey,
     pub vault_usdc: Pubkey,
     pub fee_vault_usdc: Pubkey,
-    pub fee_bps: u16,
+    pub maker_fee_bps: i16, // negative pays makers a rebate out of the taker fee
+    pub taker_fee_bps: u16,
+    pub fee_curve: FeeCurve,
     pub cranker_reward_bps: u16,
-    pub q_yes: u128,
-    pub q_no: u128,
-    pub b_liquidity: u64,
+    pub match_reward: u64,
+    pub crank_rewards_owed: u64,
+    pub referral_rewards_owed: u64,
+    pub q_outcomes: [u128; MAX_OUTCOMES],
+    pub b_liquidity: u64, // derived from amm_subsidy, zero when the market has no AMM
+    pub amm_subsidy: u64, // USDC the creator deposited to fund the AMM's worst-case loss
     pub bids: Pubkey,
     pub asks: Pubkey,
     pub request_queue: Pubkey,
     pub event_queue: Pubkey,
     pub oracle: Pubkey,
+    pub resolution_mode: ResolutionMode,
+    pub proposal_bond: u64,
+    pub liveness_secs: i64,
+    pub price_feed: Pubkey,
+    pub price_feed_program: Pubkey, // oracle program that must own the price_feed account
+    pub strike_price: i64,
+    pub strike_expo: i32,
+    pub max_staleness_secs: i64,
+    pub max_confidence_bps: u16,
+    pub feed_snapshot: PriceFeed, // latest price recorded from the observation window, zeroed until then
+    pub resolved_value: i64,
+    pub payout_numerators: [u64; MAX_OUTCOMES],
+    pub payout_denominator: u64,
+    pub resolved_at: i64,
+    pub challenge_period_secs: i64, // claims stay closed this long after resolved_at
+    pub resolution_deadline: i64,   // past this anyone can cancel a market that was never resolved
+    pub refund_mode: RefundMode,
+    pub net_deposits: [u64; MAX_OUTCOMES], // USDC collateral that entered the vault for each outcome
+    pub quote_escrow: u64, // USDC in vault_usdc owed back to OpenOrders, free and locked
+    pub outcome_supply: [u64; MAX_OUTCOMES], // mint supplies less any burns holders did themselves
+    pub event_seq: u64,
     pub bump: u8,
 }
+
+impl Market {
+    // time based transitions are applied lazily by whichever instruction touches the market next
+    pub fn refresh_status(&mut self, now: i64) {
+        if self.status == MarketStatus::Pending
+            && now >= self.start_ts
+            && self.registered_outcomes == self.num_outcomes
+        {
+            self.status = MarketStatus::Open;
+        }
+        if (self.status == MarketStatus::Open || self.status == MarketStatus::Paused)
+            && now >= self.end_ts
+        {
+            self.status = MarketStatus::Closed;
+        }
+    }
+
+    pub fn require_trading(&mut self, now: i64) -> Result<()> {
+        self.refresh_status(now);
+        match self.status {
+            MarketStatus::Open => Ok(()),
+            MarketStatus::Paused => err!(MarketError::MarketPaused),
+            _ => err!(MarketError::MarketNotOpen),
+        }
+    }
+
+    // binary markets keep their dedicated YES / NO statuses, categorical ones only record the winner
+    pub fn apply_resolution(&mut self, winner: u8, now: i64) -> Result<()> {
+        require!(
+            self.kind != MarketKind::Scalar,
+            MarketError::InvalidMarketStatus
+        );
+        require!(winner < self.num_outcomes, MarketError::InvalidArgument);
+
+        self.payout_numerators = [0; MAX_OUTCOMES];
+        self.payout_numerators[winner as usize] = 1;
+        self.payout_denominator = 1;
+        self.resolved_at = now;
+        self.status = match self.kind {
+            MarketKind::Binary if winner == OutcomeSide::Yes as u8 => MarketStatus::ResolvedYes,
+            MarketKind::Binary => MarketStatus::ResolvedNo,
+            _ => MarketStatus::Resolved,
+        };
+        Ok(())
+    }
+
+    // CTF style payout vector, outcome i redeems for numerators[i] / sum(numerators) USDC
+    pub fn apply_payout_vector(&mut self, numerators: &[u64], now: i64) -> Result<()> {
+        require!(
+            numerators.len() == self.num_outcomes as usize,
+            MarketError::InvalidPayoutVector
+        );
+        let denominator = numerators.iter().try_fold(0u64, |sum, numerator| {
+            sum.checked_add(*numerator)
+                .ok_or(error!(MarketError::MathError))
+        })?;
+        require!(denominator > 0, MarketError::InvalidPayoutVector);
+
+        self.payout_numerators = [0; MAX_OUTCOMES];
+        self.payout_numerators[..numerators.len()].copy_from_slice(numerators);
+        self.payout_denominator = denominator;
+        self.resolved_at = now;
+        self.status = MarketStatus::Resolved;
+        Ok(())
+    }
+
+    // a voided market refunds every outcome equally, so a complete set still redeems for 1 USDC
+    pub fn apply_void(&mut self, now: i64) {
+        self.payout_numerators = [0; MAX_OUTCOMES];
+        self.payout_numerators[..self.num_outcomes as usize].fill(1);
+        self.payout_denominator = self.num_outcomes as u64;
+        self.resolved_at = now;
+        self.status = MarketStatus::Cancelled;
+    }
+
+    // refund rates are fixed here, NetDeposits needs the current supply of every outcome mint; each
+    // outcome is refunded at its own rate, min(net_deposits / supply, 1), so unlike every other
+    // payout vector the numerators need not sum to the denominator and a complete set is worth
+    // complete_set_value rather than 1 USDC
+    pub fn apply_cancellation(&mut self, supplies: &[u64], now: i64) -> Result<()> {
+        if self.refund_mode == RefundMode::Even {
+            self.apply_void(now);
+            return Ok(());
+        }
+        require!(
+            supplies.len() == self.num_outcomes as usize,
+            MarketError::InvalidArgument
+        );
+
+        let mut numerators = [0u64; MAX_OUTCOMES];
+        for (outcome, supply) in supplies.iter().enumerate() {
+            if *supply == 0 {
+                continue;
+            }
+            let rate = (self.net_deposits[outcome] as u128)
+                .checked_mul(PRICE_PRECISION_SCALE)
+                .ok_or(MarketError::MathError)?
+                .checked_div(*supply as u128)
+                .ok_or(MarketError::MathError)?;
+            numerators[outcome] = rate.min(PRICE_PRECISION_SCALE) as u64;
+        }
+
+        // nothing was ever deposited, fall back to an even split
+        if numerators.iter().all(|numerator| *numerator == 0) {
+            self.apply_void(now);
+            return Ok(());
+        }
+
+        self.payout_numerators = numerators;
+        self.payout_denominator = PRICE_PRECISION_SCALE as u64;
+        self.resolved_at = now;
+        self.status = MarketStatus::Cancelled;
+        Ok(())
+    }
+
+    // a complete set backs every outcome equally, any remainder stays in the vault as dust
+    pub fn record_complete_set(&mut self, amount: u64, deposit: bool) -> Result<()> {
+        let share = amount / self.num_outcomes as u64;
+        for net_deposit in self.net_deposits[..self.num_outcomes as usize].iter_mut() {
+            *net_deposit = if deposit {
+                net_deposit
+                    .checked_add(share)
+                    .ok_or(MarketError::MathError)?
+            } else {
+                net_deposit.saturating_sub(share)
+            };
+        }
+        Ok(())
+    }
+
+    // fee at `bps` on the fee base, rounded down so it never exceeds the rate
+    pub fn fee_on(base: u64, bps: u16) -> Result<u64> {
+        let fee = (base as u128)
+            .checked_mul(bps as u128)
+            .ok_or(MarketError::MathError)?
+            .checked_div(10_000u128)
+            .ok_or(MarketError::MathError)?;
+        Ok(fee as u64)
+    }
+
+    // what the fee rates apply to on a fill of `quantity` outcome tokens costing `notional` USDC,
+    // a winning token redeems for one USDC so (1 - p) * quantity is quantity - notional
+    pub fn fee_base(&self, notional: u64, quantity: u64) -> u64 {
+        match self.fee_curve {
+            FeeCurve::Flat => notional,
+            FeeCurve::Probability => notional.min(quantity.saturating_sub(notional)),
+        }
+    }
+
+    // USDC a buy order locks on top of its notional so it can pay the taker fee if it crosses; on
+    // the probability curve a fill below the limit price can cost more, so that reserves for p <= 1/2
+    pub fn taker_fee_reserve(&self, notional: u64, quantity: u64) -> Result<u64> {
+        let base = match self.fee_curve {
+            FeeCurve::Flat => notional,
+            FeeCurve::Probability => notional.min(quantity / 2),
+        };
+        Self::fee_on(base, self.taker_fee_bps)
+    }
+
+    // (taker fee, maker fee) on a fill at the taker's rate; a negative maker fee is a rebate funded
+    // by the taker fee, capped by it when the taker's tier discounts the fee below the rebate
+    pub fn fill_fees(
+        &self,
+        notional: u64,
+        quantity: u64,
+        taker_fee_bps: u16,
+    ) -> Result<(u64, i64)> {
+        let base = self.fee_base(notional, quantity);
+        let taker_fee = Self::fee_on(base, taker_fee_bps)?;
+        let maker_fee = Self::fee_on(base, self.maker_fee_bps.unsigned_abs())?;
+        if self.maker_fee_bps < 0 {
+            return Ok((taker_fee, -(maker_fee.min(taker_fee) as i64)));
+        }
+        Ok((taker_fee, maker_fee as i64))
+    }
+
+    // what in the fee vault isn't already promised to crankers and referrers
+    pub fn collectable_fees(&self, fee_vault_balance: u64) -> u64 {
+        fee_vault_balance
+            .saturating_sub(self.crank_rewards_owed)
+            .saturating_sub(self.referral_rewards_owed)
+    }
+
+    pub fn lock_quote(&mut self, amount: u64) -> Result<()> {
+        self.quote_escrow = self
+            .quote_escrow
+            .checked_add(amount)
+            .ok_or(MarketError::MathError)?;
+        Ok(())
+    }
+
+    pub fn release_quote(&mut self, amount: u64) -> Result<()> {
+        self.quote_escrow = self
+            .quote_escrow
+            .checked_sub(amount)
+            .ok_or(MarketError::MathError)?;
+        Ok(())
+    }
+
+    pub fn record_mint(&mut self, outcome: u8, amount: u64) -> Result<()> {
+        let supply = &mut self.outcome_supply[outcome as usize];
+        *supply = supply.checked_add(amount).ok_or(MarketError::MathError)?;
+        Ok(())
+    }
+
+    pub fn record_burn(&mut self, outcome: u8, amount: u64) -> Result<()> {
+        let supply = &mut self.outcome_supply[outcome as usize];
+        *supply = supply.checked_sub(amount).ok_or(MarketError::MathError)?;
+        Ok(())
+    }
+
+    // USDC the vault must hold for the given outcome supplies: the exact payout once resolved,
+    // otherwise the worst case, which is every token of the largest outcome paying 1 USDC
+    pub fn outcome_liability(&self, supplies: &[u64]) -> Result<u64> {
+        if !self.is_resolved() {
+            return Ok(supplies.iter().copied().max().unwrap_or(0));
+        }
+        supplies
+            .iter()
+            .enumerate()
+            .try_fold(0u64, |total, (outcome, supply)| {
+                // rounded up so the audit never understates what claims can take out
+                let owed = (*supply as u128)
+                    .checked_mul(self.payout_numerators[outcome] as u128)
+                    .ok_or(MarketError::MathError)?
+                    .div_ceil(self.payout_denominator as u128);
+                total
+                    .checked_add(owed as u64)
+                    .ok_or(error!(MarketError::MathError))
+            })
+    }
+
+    // values outside the range are clamped, so LONG pays (v - lo) / (hi - lo) and SHORT the rest
+    pub fn apply_scalar_resolution(&mut self, value: i64, now: i64) -> Result<()> {
+        require!(
+            self.kind == MarketKind::Scalar,
+            MarketError::InvalidMarketStatus
+        );
+        let clamped = value.clamp(self.scalar_lower, self.scalar_upper);
+        let range = self
+            .scalar_upper
+            .checked_sub(self.scalar_lower)
+            .ok_or(MarketError::MathError)?;
+        let long = clamped
+            .checked_sub(self.scalar_lower)
+            .ok_or(MarketError::MathError)?;
+
+        self.resolved_value = value;
+        self.payout_numerators = [0; MAX_OUTCOMES];
+        self.payout_numerators[0] = long as u64;
+        self.payout_numerators[1] = (range - long) as u64;
+        self.payout_denominator = range as u64;
+        self.resolved_at = now;
+        self.status = MarketStatus::Resolved;
+        Ok(())
+    }
+
+    // USDC owed for `amount` tokens of the given outcome index once the market is resolved
+    pub fn payout_for(&self, outcome_index: usize, amount: u64) -> Result<u64> {
+        require!(self.payout_denominator > 0, MarketError::MarketNotOpen);
+        require!(
+            outcome_index < self.num_outcomes as usize,
+            MarketError::InvalidArgument
+        );
+        let numerator = self.payout_numerators[outcome_index];
+        let payout = (amount as u128)
+            .checked_mul(numerator as u128)
+            .ok_or(MarketError::MathError)?
+            .checked_div(self.payout_denominator as u128)
+            .ok_or(MarketError::MathError)?;
+        Ok(payout as u64)
+    }
+
+    // USDC that `amount` complete sets are worth: 1 USDC each until a payout vector is written,
+    // afterwards exactly what claiming one token of every outcome would pay
+    pub fn complete_set_value(&self, amount: u64) -> Result<u64> {
+        if self.payout_denominator == 0 {
+            return Ok(amount);
+        }
+        let mut value = 0u64;
+        for outcome in 0..self.num_outcomes as usize {
+            value = value
+                .checked_add(self.payout_for(outcome, amount)?)
+                .ok_or(MarketError::MathError)?;
+        }
+        Ok(value)
+    }
+
+    pub fn outcome_index(&self, outcome_mint: &Pubkey) -> Result<usize> {
+        self.outcome_mints[..self.num_outcomes as usize]
+            .iter()
+            .position(|mint| mint == outcome_mint)
+            .ok_or(error!(MarketError::InvalidOutcome))
+    }
+
+    pub fn check_outcome_vault(&self, outcome: u8, vault: &Pubkey) -> Result<()> {
+        require!(outcome < self.num_outcomes, MarketError::InvalidOutcome);
+        require!(
+            self.outcome_vaults[outcome as usize] == *vault,
+            MarketError::InvalidOutcome
+        );
+        Ok(())
+    }
+
+    // the feed price a PriceFeed market resolves on: the live one while it still shows a price from
+    // the observation window, otherwise the latest snapshot_feed_price recorded from it
+    pub fn observed_price(&self, live: PriceFeed) -> Result<PriceFeed> {
+        let snapshot = self.feed_snapshot;
+        if live
+            .check_staleness(self.end_ts, self.max_staleness_secs)
+            .is_ok()
+            && live.publish_time >= snapshot.publish_time
+        {
+            return Ok(live);
+        }
+        require!(snapshot.publish_time > 0, MarketError::StalePrice);
+        Ok(snapshot)
+    }
+
+    pub fn claims_open_at(&self) -> Result<i64> {
+        let open_at = self
+            .resolved_at
+            .checked_add(self.challenge_period_secs)
+            .ok_or(MarketError::MathError)?;
+        Ok(open_at)
+    }
+
+    // a resolution is only final once its challenge period is over, until then the oracle of an
+    // Authority market may correct it
+    pub fn require_claimable(&self, now: i64) -> Result<()> {
+        require!(self.is_resolved(), MarketError::MarketNotOpen);
+        require!(
+            now >= self.claims_open_at()?,
+            MarketError::ChallengePeriodActive
+        );
+        Ok(())
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        matches!(
+            self.status,
+            MarketStatus::ResolvedYes
+                | MarketStatus::ResolvedNo
+                | MarketStatus::Resolved
+                | MarketStatus::Cancelled
+        )
+    }
+
+    pub fn next_event_seq(&mut self) -> Result<u64> {
+        self.event_seq = self
+            .event_seq
+            .checked_add(1)
+            .ok_or(MarketError::MathError)?;
+        Ok(self.event_seq)
+    }
+}
@@ -1,13 +1,27 @@
+pub mod committee;
+pub mod cranker_ledger;
 pub mod event_queue;
+pub mod global_config;
 pub mod market;
 pub mod open_orders;
 pub mod order_book;
+pub mod price_feed;
+pub mod referrer_ledger;
 pub mod request_queue;
+pub mod resolution;
+pub mod trader_stats;
 pub mod vault;
 
+pub use committee::*;
+pub use cranker_ledger::*;
 pub use event_queue::*;
+pub use global_config::*;
 pub use market::*;
 pub use open_orders::*;
 pub use order_book::*;
+pub use price_feed::*;
+pub use referrer_ledger::*;
 pub use request_queue::*;
+pub use resolution::*;
+pub use trader_stats::*;
 pub use vault::*;
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::constants::MAX_OPEN_ORDER_SLOTS;
+use crate::constants::{MAX_OPEN_ORDER_SLOTS, MAX_OUTCOMES};
 
 #[repr(C)]
 #[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
@@ -17,11 +17,12 @@ pub struct OrderSlot {
 pub struct OpenOrder {
     pub owner: Pubkey,
     pub market: Pubkey,
-    pub free_base: u128,
+    pub free_base: [u128; MAX_OUTCOMES], // outcome tokens held for the owner in each outcome vault
     pub free_quote: u128,
     pub locked_base: u128,
     pub locked_quote: u128,
     pub slots_bitmaps: u128,
     pub slots: [OrderSlot; MAX_OPEN_ORDER_SLOTS],
+    pub referrer: Pubkey, // earns a share of this owner's taker fees, default when none
     pub bump: u8,
 }
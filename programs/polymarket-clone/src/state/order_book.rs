@@ -4,7 +4,6 @@ use crate::constants::{MAX_ORDER_ENTRIES, MAX_PRICE_NODES};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(C)]
-
 pub struct OrderEntry {
     pub occupied: bool,
     pub order_id: u64,
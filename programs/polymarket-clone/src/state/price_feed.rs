@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+
+use crate::error::MarketError;
+
+// magic tag Pyth uses at the start of its price accounts, reused so mocks are easy to spot
+pub const PRICE_FEED_MAGIC: u32 = 0xa1b2_c3d4;
+pub const PRICE_FEED_LEN: usize = 32;
+const MAX_EXPO_DIFF: u32 = 18;
+
+// Layout of a price feed account, all fields little endian:
+//
+//   offset  size  field         meaning
+//   0       4     magic         u32, must equal PRICE_FEED_MAGIC
+//   4       4     expo          i32, real price = price * 10^expo
+//   8       8     price         i64, aggregate price
+//   16      8     conf          u64, confidence interval in the same exponent as price
+//   24      8     publish_time  i64, unix seconds the price was published
+//
+// Anything after byte 32 is ignored so larger accounts with a compatible prefix also parse.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PriceFeed {
+    pub expo: i32,
+    pub price: i64,
+    pub conf: u64,
+    pub publish_time: i64,
+}
+
+impl PriceFeed {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        require!(data.len() >= PRICE_FEED_LEN, MarketError::InvalidPriceFeed);
+
+        let magic = u32::from_le_bytes(field(data, 0)?);
+        require!(magic == PRICE_FEED_MAGIC, MarketError::InvalidPriceFeed);
+
+        Ok(PriceFeed {
+            expo: i32::from_le_bytes(field(data, 4)?),
+            price: i64::from_le_bytes(field(data, 8)?),
+            conf: u64::from_le_bytes(field(data, 16)?),
+            publish_time: i64::from_le_bytes(field(data, 24)?),
+        })
+    }
+
+    // the price has to be published in the observation window, at or shortly before end_ts; a
+    // later print would let the resolver pick one after the fact
+    pub fn check_staleness(&self, target_ts: i64, max_staleness_secs: i64) -> Result<()> {
+        require!(self.publish_time <= target_ts, MarketError::StalePrice);
+        let age = target_ts
+            .checked_sub(self.publish_time)
+            .ok_or(MarketError::MathError)?;
+        require!(age <= max_staleness_secs, MarketError::StalePrice);
+        Ok(())
+    }
+
+    pub fn check_confidence(&self, max_confidence_bps: u16) -> Result<()> {
+        require!(self.price > 0, MarketError::InvalidPriceFeed);
+        let conf_scaled = (self.conf as u128)
+            .checked_mul(10_000)
+            .ok_or(MarketError::MathError)?;
+        let allowed = (self.price as u128)
+            .checked_mul(max_confidence_bps as u128)
+            .ok_or(MarketError::MathError)?;
+        require!(conf_scaled <= allowed, MarketError::PriceConfidenceTooWide);
+        Ok(())
+    }
+
+    // compares price * 10^expo against strike * 10^strike_expo without losing precision
+    pub fn is_above(&self, strike: i64, strike_expo: i32) -> Result<bool> {
+        let diff = (self.expo as i64)
+            .checked_sub(strike_expo as i64)
+            .ok_or(MarketError::MathError)?;
+        require!(
+            diff.unsigned_abs() <= MAX_EXPO_DIFF as u64,
+            MarketError::InvalidPriceFeed
+        );
+        let scale = 10i128
+            .checked_pow(diff.unsigned_abs() as u32)
+            .ok_or(MarketError::MathError)?;
+
+        let (price, strike) = if diff >= 0 {
+            (
+                (self.price as i128)
+                    .checked_mul(scale)
+                    .ok_or(MarketError::MathError)?,
+                strike as i128,
+            )
+        } else {
+            (
+                self.price as i128,
+                (strike as i128)
+                    .checked_mul(scale)
+                    .ok_or(MarketError::MathError)?,
+            )
+        };
+        Ok(price > strike)
+    }
+}
+
+fn field<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N]> {
+    let bytes = data
+        .get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(MarketError::InvalidPriceFeed)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // writes a mock price account with the documented layout
+    fn mock_price_account(expo: i32, price: i64, conf: u64, publish_time: i64) -> Vec<u8> {
+        let mut data = Vec::with_capacity(PRICE_FEED_LEN);
+        data.extend_from_slice(&PRICE_FEED_MAGIC.to_le_bytes());
+        data.extend_from_slice(&expo.to_le_bytes());
+        data.extend_from_slice(&price.to_le_bytes());
+        data.extend_from_slice(&conf.to_le_bytes());
+        data.extend_from_slice(&publish_time.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn parses_documented_layout() {
+        let data = mock_price_account(-8, 6_500_000_000_000, 1_000_000_000, 1_700_000_000);
+        let feed = PriceFeed::parse(&data).unwrap();
+        assert_eq!(
+            feed,
+            PriceFeed {
+                expo: -8,
+                price: 6_500_000_000_000,
+                conf: 1_000_000_000,
+                publish_time: 1_700_000_000,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_bad_magic_and_short_accounts() {
+        let mut data = mock_price_account(-8, 1, 0, 0);
+        data[0] ^= 0xff;
+        assert!(PriceFeed::parse(&data).is_err());
+        assert!(PriceFeed::parse(&data[..PRICE_FEED_LEN - 1]).is_err());
+    }
+
+    #[test]
+    fn staleness_only_accepts_prices_up_to_target_time() {
+        let feed = PriceFeed::parse(&mock_price_account(-8, 1, 0, 1_000)).unwrap();
+        assert!(feed.check_staleness(1_000, 0).is_ok());
+        assert!(feed.check_staleness(1_060, 60).is_ok());
+        assert!(feed.check_staleness(1_061, 60).is_err());
+        // published after the target time, however close
+        assert!(feed.check_staleness(999, 60).is_err());
+        assert!(feed.check_staleness(940, 60).is_err());
+    }
+
+    #[test]
+    fn confidence_is_bounded_relative_to_price() {
+        // 1% confidence on a price of 100
+        let feed = PriceFeed::parse(&mock_price_account(0, 100, 1, 0)).unwrap();
+        assert!(feed.check_confidence(100).is_ok());
+        assert!(feed.check_confidence(99).is_err());
+
+        let negative = PriceFeed::parse(&mock_price_account(0, -100, 1, 0)).unwrap();
+        assert!(negative.check_confidence(10_000).is_err());
+    }
+
+    #[test]
+    fn compares_against_strike_across_exponents() {
+        // 65_000.00000000 against a strike of 60_000
+        let feed = PriceFeed::parse(&mock_price_account(-8, 6_500_000_000_000, 0, 0)).unwrap();
+        assert!(feed.is_above(60_000, 0).unwrap());
+        assert!(!feed.is_above(70_000, 0).unwrap());
+        assert!(feed.is_above(6_499_999, -2).unwrap());
+        assert!(!feed.is_above(6_500_000, -2).unwrap());
+
+        // a price exactly at the strike does not count as above
+        let at_strike = PriceFeed::parse(&mock_price_account(2, 600, 0, 0)).unwrap();
+        assert!(!at_strike.is_above(60_000, 0).unwrap());
+        assert!(at_strike.is_above(59_999, 0).unwrap());
+    }
+}
@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct ReferrerLedger {
+    pub referrer: Pubkey,
+    pub market: Pubkey,
+    pub accrued: u64,
+    pub total_claimed: u64,
+    pub referred_fills: u64,
+    pub referred_volume: u64, // taker notional of the referred fills
+    pub referred_fees: u64,   // taker fees those fills paid
+    pub bump: u8,
+}
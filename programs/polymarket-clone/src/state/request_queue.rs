@@ -40,6 +40,7 @@ pub struct Request {
     pub request_type: u8,
     pub owner: Pubkey,
     pub open_order: Pubkey,
+    pub owner_slot: u16,
     pub side: u8,
     pub price: u128,
     pub quantity: u64,
@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct ResolutionProposal {
+    pub market: Pubkey,
+    pub proposer: Pubkey,
+    pub proposed_outcome: u8,
+    pub proposed_at: i64,
+    pub bond: u64,
+    pub disputer: Pubkey,
+    pub disputed_at: i64,
+    pub disputed_outcome: u8, // what the disputer argues for instead of proposed_outcome
+    pub settled: bool,
+    pub bump: u8,
+}
+
+impl ResolutionProposal {
+    pub fn is_disputed(&self) -> bool {
+        self.disputer != Pubkey::default()
+    }
+}
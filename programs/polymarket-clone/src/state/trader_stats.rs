@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{SECONDS_PER_DAY, VOLUME_WINDOW_DAYS},
+    error::MarketError,
+};
+
+// one per trader across every market, fills are bucketed by day so the window can roll cheaply
+#[account]
+pub struct TraderStats {
+    pub trader: Pubkey,
+    pub daily_notional: [u64; VOLUME_WINDOW_DAYS],
+    pub last_day: i64,
+    pub lifetime_notional: u64,
+    pub bump: u8,
+}
+
+impl TraderStats {
+    // clears the buckets of every day skipped since the last fill
+    pub fn roll(&mut self, now: i64) {
+        let day = now.div_euclid(SECONDS_PER_DAY);
+        if day <= self.last_day {
+            return;
+        }
+        let stale_days = (day - self.last_day).min(VOLUME_WINDOW_DAYS as i64);
+        for offset in 0..stale_days {
+            let bucket = (day - offset).rem_euclid(VOLUME_WINDOW_DAYS as i64) as usize;
+            self.daily_notional[bucket] = 0;
+        }
+        self.last_day = day;
+    }
+
+    pub fn rolling_notional(&self) -> u64 {
+        self.daily_notional
+            .iter()
+            .fold(0u64, |total, day| total.saturating_add(*day))
+    }
+
+    pub fn record_fill(&mut self, now: i64, notional: u64) -> Result<()> {
+        self.roll(now);
+        let bucket = self.last_day.rem_euclid(VOLUME_WINDOW_DAYS as i64) as usize;
+        self.daily_notional[bucket] = self.daily_notional[bucket]
+            .checked_add(notional)
+            .ok_or(MarketError::MathError)?;
+        self.lifetime_notional = self
+            .lifetime_notional
+            .checked_add(notional)
+            .ok_or(MarketError::MathError)?;
+        Ok(())
+    }
+}
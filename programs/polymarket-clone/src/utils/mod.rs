@@ -1,5 +1,9 @@
+pub mod order_slots;
 pub mod ring_buffer;
 pub mod slab;
+pub mod solvency;
 
+pub use order_slots::*;
 pub use ring_buffer::*;
 pub use slab::*;
+pub use solvency::*;
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::MAX_OPEN_ORDER_SLOTS,
+    error::MarketError,
+    state::{OpenOrder, OrderSlot},
+};
+
+pub fn allocate_order_slot(open_order: &mut OpenOrder, slot: OrderSlot) -> Result<u16> {
+    for i in 0..MAX_OPEN_ORDER_SLOTS {
+        if open_order.slots_bitmaps & (1u128 << i) == 0 {
+            open_order.slots_bitmaps |= 1u128 << i;
+            open_order.slots[i] = OrderSlot {
+                active: true,
+                ..slot
+            };
+            return Ok(i as u16);
+        }
+    }
+    err!(MarketError::MaxOrderReached)
+}
+
+pub fn find_order_slot(open_order: &OpenOrder, order_id: u64) -> Option<u16> {
+    open_order
+        .slots
+        .iter()
+        .position(|slot| slot.active && slot.order_id == order_id)
+        .map(|index| index as u16)
+}
+
+pub fn release_order_slot(open_order: &mut OpenOrder, slot_index: u16) -> Result<()> {
+    let index = slot_index as usize;
+    if index >= MAX_OPEN_ORDER_SLOTS {
+        return err!(MarketError::InvalidArgument);
+    }
+    open_order.slots_bitmaps &= !(1u128 << index);
+    open_order.slots[index].active = false;
+    open_order.slots[index].quantity_remaining = 0;
+    Ok(())
+}
+
+// reduces the remaining quantity on a slot after a fill and frees it once nothing is left
+pub fn fill_order_slot(open_order: &mut OpenOrder, slot_index: u16, quantity: u64) -> Result<()> {
+    let index = slot_index as usize;
+    if index >= MAX_OPEN_ORDER_SLOTS || !open_order.slots[index].active {
+        return err!(MarketError::OrderNotFound);
+    }
+    let remaining = open_order.slots[index]
+        .quantity_remaining
+        .checked_sub(quantity)
+        .ok_or(MarketError::MathError)?;
+    open_order.slots[index].quantity_remaining = remaining;
+    if remaining == 0 {
+        release_order_slot(open_order, slot_index)?;
+    }
+    Ok(())
+}
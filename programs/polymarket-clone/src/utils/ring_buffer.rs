@@ -40,9 +40,29 @@ pub fn dequeue_requests(queue: &mut RequestQueue, n: usize) -> Result<Vec<Reques
     Ok(output_queue)
 }
 
+pub fn peek_request(queue: &RequestQueue) -> Option<Request> {
+    if queue.count == 0 {
+        return None;
+    }
+    Some(queue.requests[(queue.head as usize) % MAX_REQUESTS])
+}
+
+pub fn update_head_request_quantity(queue: &mut RequestQueue, quantity: u64) -> Result<()> {
+    if queue.count == 0 {
+        return err!(MarketError::NoMatchingOrder);
+    }
+    let index = (queue.head as usize) % MAX_REQUESTS;
+    queue.requests[index].quantity = quantity;
+    Ok(())
+}
+
+pub fn event_queue_has_space(queue: &EventQueue) -> bool {
+    (queue.count as usize) < MAX_EVENTS
+}
+
 pub fn push_event(queue: &mut EventQueue, event: Event) -> Result<()> {
-    if (queue.count as usize) >= MAX_EVENTS {
-        return err!(MarketError::MathError);
+    if !event_queue_has_space(queue) {
+        return err!(MarketError::EventQueueFUll);
     }
 
     let index = ((queue.head + queue.count) as usize) % MAX_EVENTS;
@@ -3,7 +3,7 @@ use anchor_lang::prelude::*;
 use crate::{
     constants::{MAX_ORDER_ENTRIES, MAX_PRICE_NODES},
     error::MarketError,
-    state::Slab,
+    state::{OrderEntry, Slab},
 };
 
 pub fn initialize_slab(slab: &mut Slab, is_bid: bool, bump: u8) {
@@ -37,11 +37,7 @@ pub fn initialize_slab(slab: &mut Slab, is_bid: bool, bump: u8) {
 
 #[inline(always)]
 fn is_null(index: i32) -> bool {
-    if index == -1 {
-        true
-    } else {
-        false
-    }
+    index == -1
 }
 
 #[inline(always)]
@@ -265,7 +261,7 @@ fn tree_min(slab: &mut Slab, mut x: i32) -> i32 {
 }
 
 fn delete_fixup(slab: &mut Slab, mut x: i32, mut x_parent: i32) {
-    while x != slab.root_price_node && (!(!is_null(x) && is_red(slab, x))) {
+    while x != slab.root_price_node && !is_red(slab, x) {
         if x_parent == -1 {
             break;
         }
@@ -452,25 +448,91 @@ pub fn pop_order_from_prices(slab: &mut Slab, price_node_index: i32) -> Result<i
     Ok(head)
 }
 
-pub fn find_best_price_node_index(slab: &Slab) -> Option<i32> {
-    let root = slab.root_price_node;
-    if root == -1 {
-        return None;
+pub fn remove_order_from_price(
+    slab: &mut Slab,
+    price_node_index: i32,
+    order_id: u64,
+    open_order_owner: Pubkey,
+) -> Result<Option<OrderEntry>> {
+    if price_node_index < 0 {
+        return err!(MarketError::NoMatchingOrder);
+    }
+
+    let mut previous = -1;
+    let mut current = slab.price_nodes[price_node_index as usize].order_head;
+    while !is_null(current) {
+        let entry = slab.order_entries[current as usize];
+        if entry.order_id == order_id && entry.open_order_owner == open_order_owner {
+            let next = entry.next_in_price;
+            if is_null(previous) {
+                slab.price_nodes[price_node_index as usize].order_head = next;
+            } else {
+                slab.order_entries[previous as usize].next_in_price = next;
+            }
+            if slab.price_nodes[price_node_index as usize].order_tail == current {
+                slab.price_nodes[price_node_index as usize].order_tail = previous;
+            }
+
+            slab.order_entries[current as usize].occupied = false;
+            slab.order_entries[current as usize].next_in_price = -1;
+
+            // an empty price level would otherwise stay in the tree and stop matching at that price
+            if is_null(slab.price_nodes[price_node_index as usize].order_head) {
+                remove_price_node(slab, price_node_index)?;
+            }
+            return Ok(Some(entry));
+        }
+        previous = current;
+        current = entry.next_in_price;
     }
+    Ok(None)
+}
+
+// every outcome has its own book inside the shared slab: price nodes are keyed by
+// (outcome << 64) | price, so an outcome's levels form one contiguous range of the tree
+pub fn book_key(outcome: u8, price: u128) -> Result<u128> {
+    require!(price <= u64::MAX as u128, MarketError::InvalidArgument);
+    Ok(((outcome as u128) << 64) | price)
+}
 
+pub fn key_price(key: u128) -> u128 {
+    key & u64::MAX as u128
+}
+
+pub fn find_best_price_node_index(slab: &Slab, outcome: u8) -> Option<i32> {
+    let lowest = (outcome as u128) << 64;
+    let highest = lowest | u64::MAX as u128;
+
+    let mut best = -1;
+    let mut current = slab.root_price_node;
     if slab.is_bid {
-        // For bids, we want the highest price (rightmost node)
-        let mut current = root;
-        while slab.price_nodes[current as usize].right != -1 {
-            current = slab.price_nodes[current as usize].right;
+        // For bids, we want the highest price of the outcome (the last key not above its range)
+        while current != -1 {
+            if slab.price_nodes[current as usize].key <= highest {
+                best = current;
+                current = slab.price_nodes[current as usize].right;
+            } else {
+                current = slab.price_nodes[current as usize].left;
+            }
         }
-        Some(current)
     } else {
-        // For asks, we want the lowest price (leftmost node)
-        let mut current = root;
-        while slab.price_nodes[current as usize].left != -1 {
-            current = slab.price_nodes[current as usize].left;
+        // For asks, we want the lowest price of the outcome (the first key not below its range)
+        while current != -1 {
+            if slab.price_nodes[current as usize].key >= lowest {
+                best = current;
+                current = slab.price_nodes[current as usize].left;
+            } else {
+                current = slab.price_nodes[current as usize].right;
+            }
         }
-        Some(current)
     }
+
+    if best == -1 {
+        return None;
+    }
+    let key = slab.price_nodes[best as usize].key;
+    if key < lowest || key > highest {
+        return None;
+    }
+    Some(best)
 }
@@ -0,0 +1,291 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::{error::MarketError, events::SolvencyAudited, state::Market};
+
+// vault_usdc >= quote_escrow + outcome_liability, where the liability covers every outstanding
+// outcome token, complete sets and AMM-issued tokens alike; until payouts are fixed an outcome is
+// counted at no less than what the AMM issued of it, so an AMM fill whose cost never reached the
+// vault shows up even when the supplies passed in miss it
+pub fn audit_solvency(
+    market: &mut Account<Market>,
+    vault_balance: u64,
+    supplies: &[u64],
+) -> Result<()> {
+    let n_outcomes = market.num_outcomes as usize;
+    require!(supplies.len() == n_outcomes, MarketError::InvalidArgument);
+    let amm_issued = market.q_outcomes[..n_outcomes]
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or(0);
+    let amm_issued = u64::try_from(amm_issued).map_err(|_| MarketError::MathError)?;
+
+    let mut counted = supplies.to_vec();
+    if !market.is_resolved() {
+        for (supply, issued) in counted.iter_mut().zip(market.q_outcomes.iter()) {
+            *supply = (*supply).max(u64::try_from(*issued).map_err(|_| MarketError::MathError)?);
+        }
+    }
+    let outcome_liability = market.outcome_liability(&counted)?;
+    let required = market
+        .quote_escrow
+        .checked_add(outcome_liability)
+        .ok_or(MarketError::MathError)?;
+    let solvent = vault_balance >= required;
+
+    emit!(SolvencyAudited {
+        seq: market.next_event_seq()?,
+        market: market.key(),
+        vault_balance,
+        quote_escrow: market.quote_escrow,
+        outcome_liability,
+        amm_issued,
+        solvent,
+    });
+
+    if !solvent {
+        msg!(
+            "insolvent: vault {} < escrow {} + outcome liability {}",
+            vault_balance,
+            market.quote_escrow,
+            outcome_liability
+        );
+        return err!(MarketError::MarketInsolvent);
+    }
+    Ok(())
+}
+
+// end-of-handler check behind the `solvency-check` feature, uses the supplies tracked on Market
+pub fn debug_check_solvency(
+    market: &mut Account<Market>,
+    vault_usdc: &mut Account<TokenAccount>,
+) -> Result<()> {
+    if cfg!(feature = "solvency-check") {
+        vault_usdc.reload()?;
+        let supplies = market.outcome_supply;
+        audit_solvency(
+            market,
+            vault_usdc.amount,
+            &supplies[..market.num_outcomes as usize],
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        instructions::consume_events::{buy_lock, unlock_buy},
+        state::{OpenOrder, OrderSide, OrderSlot},
+        utils::{allocate_order_slot, fill_order_slot, release_order_slot},
+    };
+    use anchor_lang::Discriminator;
+    use anchor_spl::token::spl_token::state::{Account as SplAccount, AccountState};
+    use solana_program::program_pack::Pack;
+
+    const UNIT: u64 = 1_000_000;
+
+    fn market_data() -> Vec<u8> {
+        let mut data = Market::DISCRIMINATOR.to_vec();
+        data.resize(8 + std::mem::size_of::<Market>(), 0);
+        data
+    }
+
+    fn open_order() -> OpenOrder {
+        OpenOrder::deserialize(&mut &vec![0u8; std::mem::size_of::<OpenOrder>()][..]).unwrap()
+    }
+
+    fn audit(market: &mut Account<Market>, vault: u64) -> Result<()> {
+        let supplies = market.outcome_supply;
+        audit_solvency(market, vault, &supplies[..2])
+    }
+
+    // what place_request moves and locks for a limit buy
+    fn place_buy(
+        market: &mut Account<Market>,
+        vault: &mut u64,
+        trader: &mut OpenOrder,
+        price: u64,
+        quantity: u64,
+    ) -> u16 {
+        let reserved = buy_lock(market, price, quantity).unwrap();
+        *vault += reserved;
+        trader.locked_quote += reserved as u128;
+        market.lock_quote(reserved).unwrap();
+        allocate_order_slot(
+            trader,
+            OrderSlot {
+                active: true,
+                order_id: 1,
+                price,
+                side: OrderSide::Buy as u8,
+                quantity_remaining: quantity,
+                outcome: 0,
+            },
+        )
+        .unwrap()
+    }
+
+    // a binary market walked through place, fill, cancel, settle and claim exactly as the handlers
+    // move the vault and the escrow, audited after every step
+    #[test]
+    fn trading_lifecycle_stays_solvent() {
+        let (key, owner, mut lamports, mut data) =
+            (Pubkey::new_unique(), crate::ID, 0, market_data());
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        let mut market = Account::<Market>::try_from(&info).unwrap();
+        market.num_outcomes = 2;
+        market.taker_fee_bps = 100;
+        let (mut alice, mut bob) = (open_order(), open_order());
+        let mut vault = 0u64;
+
+        // alice bids 0.60 for 10 YES, locking the notional and the 1% taker fee reserve
+        let bid = place_buy(&mut market, &mut vault, &mut alice, 600_000, 10 * UNIT);
+        assert_eq!(vault, 6_060_000);
+        audit(&mut market, vault).unwrap();
+
+        // bob shorts YES with auto_split: 10 complete sets, the YES side locked for his ask
+        vault += 10 * UNIT;
+        market.record_mint(0, 10 * UNIT).unwrap();
+        market.record_mint(1, 10 * UNIT).unwrap();
+        market.record_complete_set(10 * UNIT, true).unwrap();
+        bob.locked_base += 10 * UNIT as u128;
+        bob.free_base[1] += 10 * UNIT as u128;
+        audit(&mut market, vault).unwrap();
+
+        // the ask takes the resting bid: bob is paid less his taker fee, the fee leaves for the
+        // fee vault and alice's lock gives back the taker fee she reserved as a maker
+        let notional = 6 * UNIT;
+        let (taker_fee, maker_fee) = market.fill_fees(notional, 10 * UNIT, 100).unwrap();
+        assert_eq!((taker_fee, maker_fee), (60_000, 0));
+        vault -= notional - taker_fee;
+        vault -= taker_fee;
+        unlock_buy(&mut market, &mut alice, bid, 10 * UNIT, notional).unwrap();
+        fill_order_slot(&mut alice, bid, 10 * UNIT).unwrap();
+        bob.locked_base -= 10 * UNIT as u128;
+        assert_eq!(alice.free_quote, 60_000);
+        audit(&mut market, vault).unwrap();
+
+        // a second bid is cancelled and refunded in full
+        let before = vault;
+        let bid = place_buy(&mut market, &mut vault, &mut alice, 400_000, 5 * UNIT);
+        audit(&mut market, vault).unwrap();
+        let refund = buy_lock(&market, 400_000, 5 * UNIT).unwrap();
+        vault -= refund;
+        alice.locked_quote -= refund as u128;
+        market.release_quote(refund).unwrap();
+        release_order_slot(&mut alice, bid).unwrap();
+        assert_eq!((vault, alice.locked_quote), (before, 0));
+        audit(&mut market, vault).unwrap();
+
+        // settle_funds pays out alice's free quote
+        let amount = alice.free_quote as u64;
+        vault -= amount;
+        alice.free_quote = 0;
+        market.release_quote(amount).unwrap();
+        assert_eq!(market.quote_escrow, 0);
+        audit(&mut market, vault).unwrap();
+
+        // YES wins, alice redeems her 10 YES and bob's 10 NO pay nothing
+        market.apply_resolution(0, 0).unwrap();
+        audit(&mut market, vault).unwrap();
+        for (outcome, amount) in [(0usize, 10 * UNIT), (1, 10 * UNIT)] {
+            vault -= market.payout_for(outcome, amount).unwrap();
+            market.record_burn(outcome as u8, amount).unwrap();
+            audit(&mut market, vault).unwrap();
+        }
+        assert_eq!(vault, 0);
+    }
+
+    #[test]
+    fn short_vault_is_insolvent() {
+        let (key, owner, mut lamports, mut data) =
+            (Pubkey::new_unique(), crate::ID, 0, market_data());
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        let mut market = Account::<Market>::try_from(&info).unwrap();
+        market.num_outcomes = 2;
+        market.taker_fee_bps = 100;
+        let mut alice = open_order();
+        let mut vault = 0u64;
+
+        place_buy(&mut market, &mut vault, &mut alice, 500_000, 2 * UNIT);
+        market.record_mint(0, 2 * UNIT).unwrap();
+        market.record_mint(1, 2 * UNIT).unwrap();
+        vault += 2 * UNIT;
+        audit(&mut market, vault).unwrap();
+
+        // a single base unit paid out twice is caught
+        assert!(audit(&mut market, vault - 1).is_err());
+        // so are outcome tokens the AMM issued but never charged for
+        market.q_outcomes[1] = 3 * UNIT as u128;
+        assert!(audit(&mut market, vault).is_err());
+    }
+
+    // the end-of-handler check only runs with the solvency-check feature
+    #[test]
+    fn debug_check_follows_the_feature() {
+        let (key, owner, mut lamports, mut data) =
+            (Pubkey::new_unique(), crate::ID, 0, market_data());
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        let mut market = Account::<Market>::try_from(&info).unwrap();
+        market.num_outcomes = 2;
+        market.record_mint(0, UNIT).unwrap();
+        market.record_mint(1, UNIT).unwrap();
+
+        // the vault holds one base unit less than the complete set it backs
+        let mut vault_data = vec![0u8; SplAccount::LEN];
+        SplAccount {
+            mint: Pubkey::new_unique(),
+            owner: key,
+            amount: UNIT - 1,
+            state: AccountState::Initialized,
+            ..SplAccount::default()
+        }
+        .pack_into_slice(&mut vault_data);
+        let (vault_key, token_program, mut vault_lamports) =
+            (Pubkey::new_unique(), anchor_spl::token::ID, 0);
+        let vault_info = AccountInfo::new(
+            &vault_key,
+            false,
+            true,
+            &mut vault_lamports,
+            &mut vault_data,
+            &token_program,
+            false,
+            0,
+        );
+        let mut vault = Account::<TokenAccount>::try_from(&vault_info).unwrap();
+
+        let result = debug_check_solvency(&mut market, &mut vault);
+        assert_eq!(result.is_err(), cfg!(feature = "solvency-check"));
+    }
+}
@@ -1,16 +1,96 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { assert } from "chai";
 import { PolymarketClone } from "../target/types/polymarket_clone";
 
 describe("polymarket-clone", () => {
-  // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.polymarketClone as Program<PolymarketClone>;
+  const admin = provider.wallet.publicKey;
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+  const [globalConfig] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global_config")],
+    program.programId
+  );
+  // anchor test deploys with the provider wallet as the upgrade authority
+  const [programData] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
+  it("only lets the upgrade authority initialize the config", async () => {
+    const intruder = Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(
+      intruder.publicKey,
+      1_000_000_000
+    );
+    await provider.connection.confirmTransaction(sig);
+    try {
+      await program.methods
+        .initializeConfig([])
+        .accountsPartial({
+          admin: intruder.publicKey,
+          program: program.programId,
+          programData,
+          globalConfig,
+        })
+        .signers([intruder])
+        .rpc();
+      assert.fail("a stranger claimed the config");
+    } catch (err) {
+      assert.include(String(err), "Unauthorized");
+    }
+  });
+
+  it("initializes the global config", async () => {
+    const tiers = [
+      { minVolume: new anchor.BN(0), takerFeeBps: 30 },
+      { minVolume: new anchor.BN(1_000_000_000), takerFeeBps: 20 },
+    ];
+    await program.methods
+      .initializeConfig(tiers)
+      .accountsPartial({
+        admin,
+        program: program.programId,
+        programData,
+        globalConfig,
+      })
+      .rpc();
+
+    const config = await program.account.globalConfig.fetch(globalConfig);
+    assert.ok(config.admin.equals(admin));
+    assert.equal(config.tierCount, 2);
+    assert.equal(config.protocolShareBps, 10_000);
+  });
+
+  it("only lets the admin change the fee tiers", async () => {
+    const intruder = Keypair.generate();
+    try {
+      await program.methods
+        .setFeeTiers([])
+        .accountsPartial({ admin: intruder.publicKey, globalConfig })
+        .signers([intruder])
+        .rpc();
+      assert.fail("a non-admin changed the fee tiers");
+    } catch (err) {
+      assert.include(String(err), "Unauthorized");
+    }
+  });
+
+  it("creates trader stats", async () => {
+    const [traderStats] = PublicKey.findProgramAddressSync(
+      [Buffer.from("trader_stats"), admin.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .createTraderStats()
+      .accountsPartial({ trader: admin, traderStats })
+      .rpc();
+
+    const stats = await program.account.traderStats.fetch(traderStats);
+    assert.ok(stats.trader.equals(admin));
   });
 });